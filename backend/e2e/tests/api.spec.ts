@@ -1,5 +1,5 @@
 import { test, expect } from '@playwright/test'
-import { BodyType, WireMock } from 'wiremock-captain'
+import { BodyType, DelayType, Fault, WireMock } from 'wiremock-captain'
 
 test('should return a message for health check', async ({ request }) => {
   const res = await request.get('/ping')
@@ -7,9 +7,74 @@ test('should return a message for health check', async ({ request }) => {
   expect((await res.body()).toString()).toEqual('pong')
 })
 
+const PROFILE = `advanced_shot {{name pour pressure 9 seconds 20 pump pressure sensor coffee}}
+profile_title {Simple pour}
+settings_profile_type settings_2c
+`
+
 test('should return Decent profile file from Visualizer', async ({
   request,
 }) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200, body: PROFILE },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14?format=tcl'
+  )
+  expect(res.status()).toEqual(200)
+  expect((await res.body()).toString()).toEqual(PROFILE)
+})
+
+test('should return normalized JSON profile with warnings', async ({
+  request,
+}) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200, body: PROFILE },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14'
+  )
+  expect(res.status()).toEqual(200)
+  expect(await res.json()).toEqual({
+    profile: {
+      version: '2',
+      legacy_profile_type: 'settings_2c',
+      type: 'advanced',
+      title: 'Simple pour',
+      steps: [
+        {
+          name: 'pour',
+          sensor: 'coffee',
+          pump: 'pressure',
+          pressure: '9',
+          seconds: '20',
+        },
+      ],
+    },
+    warnings: [
+      '`flow` is missing in step 1, the DE1 default is used',
+      '`transition` is missing in step 1, the DE1 default is used',
+    ],
+  })
+})
+
+test('should reject a file that is not a profile', async ({ request }) => {
   const mock = new WireMock('http://localhost:18080')
   await mock.clearAll()
   await mock.register(
@@ -22,8 +87,238 @@ test('should return Decent profile file from Visualizer', async ({
   )
 
   const res = await request.get(
-    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14?from=visualizer'
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14'
+  )
+  expect(res.status()).toEqual(422)
+  expect((await res.json()).error).toEqual('invalid_profile')
+})
+
+test('should return 404 for an unknown shot', async ({ request }) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/00000000-0000-0000-0000-000000000000/profile',
+    },
+    { status: 404, body: 'Not Found' },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/00000000-0000-0000-0000-000000000000'
+  )
+  expect(res.status()).toEqual(404)
+  expect(await res.json()).toEqual({
+    error: 'shot_not_found',
+    message: 'Shot 00000000-0000-0000-0000-000000000000 was not found',
+  })
+})
+
+test('should return 502 when Visualizer fails', async ({ request }) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 500, body: 'Internal Server Error' },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14'
+  )
+  expect(res.status()).toEqual(502)
+  expect((await res.json()).error).toEqual('upstream_unavailable')
+})
+
+test('should return 502 when Visualizer drops the connection', async ({
+  request,
+}) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200 },
+    { fault: Fault.CONNECTION_RESET_BY_PEER }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14'
+  )
+  expect(res.status()).toEqual(502)
+  expect((await res.json()).error).toEqual('upstream_unavailable')
+})
+
+test('should return 504 when Visualizer does not respond in time', async ({
+  request,
+}) => {
+  // NOTE: Longer than the default VISUALIZER_TIMEOUT_SECS of the backend
+  test.setTimeout(60000)
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200, body: 'THIS IS AWESOME PROFILE' },
+    {
+      responseBodyType: 'body' as BodyType,
+      delay: { type: DelayType.FIXED, constantDelay: 15000 },
+    }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14'
+  )
+  expect(res.status()).toEqual(504)
+  expect((await res.json()).error).toEqual('upstream_timeout')
+})
+
+test('should render the profile chart as SVG', async ({ request }) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200, body: PROFILE },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14/chart.svg?width=400&height=200'
   )
   expect(res.status()).toEqual(200)
-  expect((await res.body()).toString()).toEqual('THIS IS AWESOME PROFILE')
+  expect(res.headers()['content-type']).toEqual('image/svg+xml')
+  expect((await res.body()).toString()).toContain(
+    'width="400" height="200" viewBox="0 0 400 200"'
+  )
+})
+
+test('should render the profile chart as PNG', async ({ request }) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/profile',
+    },
+    { status: 200, body: PROFILE },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14/chart.png?width=400&height=200'
+  )
+  expect(res.status()).toEqual(200)
+  expect(res.headers()['content-type']).toEqual('image/png')
+  const body = await res.body()
+  expect(body.subarray(1, 4).toString()).toEqual('PNG')
+  expect(body.readUInt32BE(16)).toEqual(400)
+  expect(body.readUInt32BE(20)).toEqual(200)
+})
+
+test('should reject a chart size out of range', async ({ request }) => {
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14/chart.png?width=10000'
+  )
+  expect(res.status()).toEqual(400)
+  expect((await res.json()).error).toEqual('invalid_params')
+})
+
+test('should reject an unknown profile format', async ({ request }) => {
+  const res = await request.get(
+    '/profiles/19a2039f-999e-4d55-8c0d-8ae472154e14?format=xml'
+  )
+  expect(res.status()).toEqual(400)
+  expect((await res.json()).error).toEqual('invalid_params')
+})
+
+const SHOT = {
+  id: '19a2039f-999e-4d55-8c0d-8ae472154e14',
+  profile_title: 'Simple pour',
+  timeframe: ['0.0', '0.25', '0.5'],
+  data: {
+    espresso_pressure: ['0.0', '1.5', '3.0'],
+    espresso_flow: ['0.0', '2.0', '2.5'],
+    espresso_weight: ['0.0', '0.0', '0.4'],
+    espresso_temperature_basket: ['88.0', '88.5', '89.0'],
+    espresso_state_change: ['0', '1', '1'],
+  },
+}
+
+test('should return normalized shot data from Visualizer', async ({
+  request,
+}) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/download',
+    },
+    { status: 200, body: SHOT }
+  )
+
+  const res = await request.get('/shots/19a2039f-999e-4d55-8c0d-8ae472154e14')
+  expect(res.status()).toEqual(200)
+  expect(await res.json()).toEqual({
+    elapsed: [0, 0.25, 0.5],
+    pressure: [0, 1.5, 3],
+    flow: [0, 2, 2.5],
+    weight: [0, 0, 0.4],
+    temperature: [88, 88.5, 89],
+  })
+})
+
+test('should reject shot data with a value that is not a number', async ({
+  request,
+}) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/19a2039f-999e-4d55-8c0d-8ae472154e14/download',
+    },
+    {
+      status: 200,
+      body: { ...SHOT, data: { espresso_flow: ['0.0', 'oops', '2.5'] } },
+    }
+  )
+
+  const res = await request.get('/shots/19a2039f-999e-4d55-8c0d-8ae472154e14')
+  expect(res.status()).toEqual(422)
+  expect(await res.json()).toEqual({
+    error: 'invalid_shot',
+    message:
+      'Shot data is invalid: `espresso_flow` has a value that is not a number at 1',
+  })
+})
+
+test('should return 404 for the data of an unknown shot', async ({
+  request,
+}) => {
+  const mock = new WireMock('http://localhost:18080')
+  await mock.clearAll()
+  await mock.register(
+    {
+      method: 'GET',
+      endpoint: '/api/shots/00000000-0000-0000-0000-000000000000/download',
+    },
+    { status: 404, body: 'Not Found' },
+    { responseBodyType: 'body' as BodyType }
+  )
+
+  const res = await request.get('/shots/00000000-0000-0000-0000-000000000000')
+  expect(res.status()).toEqual(404)
+  expect((await res.json()).error).toEqual('shot_not_found')
 })
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use profile_core::chart::Chart;
+use resvg::{tiny_skia, usvg, usvg::fontdb};
+
+/// Renders charts to PNG with the system fonts, loaded once for the axis labels.
+pub struct Rasterizer {
+    fonts: Arc<fontdb::Database>,
+}
+
+impl Rasterizer {
+    pub fn new() -> Self {
+        let mut fonts = fontdb::Database::new();
+        fonts.load_system_fonts();
+        // NOTE: The default `Arial` isn't installed on the slim images, see the Dockerfile
+        fonts.set_sans_serif_family("DejaVu Sans");
+        Self {
+            fonts: Arc::new(fonts),
+        }
+    }
+
+    /// Rasterizes the chart onto a white background, the way chat apps expect previews.
+    pub fn png(&self, chart: &Chart) -> Result<Vec<u8>, String> {
+        let options = usvg::Options {
+            fontdb: self.fonts.clone(),
+            ..Default::default()
+        };
+        png(chart, &options)
+    }
+}
+
+fn png(chart: &Chart, options: &usvg::Options) -> Result<Vec<u8>, String> {
+    let tree = usvg::Tree::from_str(&chart.to_svg(), options).map_err(|e| e.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("chart size is empty")?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use profile_core::profile::AnalyzedProfile;
+
+    #[test]
+    fn test_png() {
+        let data = AnalyzedProfile {
+            pressure: vec![(0., 0., 0., 6.), (0., 6., 25., 6.)],
+            elapsed_time: 25.,
+            ..Default::default()
+        };
+        let png = Rasterizer::new()
+            .png(&Chart::new(&data, (320., 200.)))
+            .unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // NOTE: IHDR width and height are the first fields after the chunk header
+        assert_eq!(&png[16..24], &[0, 0, 1, 64, 0, 0, 0, 200]);
+    }
+}
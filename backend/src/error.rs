@@ -0,0 +1,98 @@
+use axum::{
+    extract::rejection::QueryRejection,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use profile_core::parser::ParseError;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum ApiError {
+    ShotNotFound(String),
+    InvalidParams(String),
+    InvalidProfile(String),
+    InvalidShot(String),
+    Render(String),
+    UpstreamUnavailable(String),
+    UpstreamTimeout,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::ShotNotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::InvalidParams(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidProfile(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::InvalidShot(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Render(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::UpstreamUnavailable(_) => StatusCode::BAD_GATEWAY,
+            ApiError::UpstreamTimeout => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::ShotNotFound(_) => "shot_not_found",
+            ApiError::InvalidParams(_) => "invalid_params",
+            ApiError::InvalidProfile(_) => "invalid_profile",
+            ApiError::InvalidShot(_) => "invalid_shot",
+            ApiError::Render(_) => "render_failed",
+            ApiError::UpstreamUnavailable(_) => "upstream_unavailable",
+            ApiError::UpstreamTimeout => "upstream_timeout",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::ShotNotFound(id) => format!("Shot {} was not found", id),
+            ApiError::InvalidParams(reason) => format!("Parameters are invalid: {}", reason),
+            ApiError::InvalidProfile(reason) => format!("Profile is invalid: {}", reason),
+            ApiError::InvalidShot(reason) => format!("Shot data is invalid: {}", reason),
+            ApiError::Render(reason) => format!("Failed to render the chart: {}", reason),
+            ApiError::UpstreamUnavailable(reason) => {
+                format!("Visualizer is unavailable: {}", reason)
+            }
+            ApiError::UpstreamTimeout => "Visualizer did not respond in time".into(),
+        }
+    }
+}
+
+impl From<ParseError> for ApiError {
+    fn from(e: ParseError) -> Self {
+        ApiError::InvalidProfile(e.to_string())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(e: QueryRejection) -> Self {
+        ApiError::InvalidParams(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ApiError::UpstreamTimeout
+        } else {
+            ApiError::UpstreamUnavailable(e.to_string())
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        tracing::warn!("{:?}", self);
+        let body = ErrorBody {
+            error: self.code(),
+            message: self.message(),
+        };
+        (self.status(), Json(body)).into_response()
+    }
+}
@@ -1,16 +1,64 @@
 use axum::{
-    extract::{Path, Query},
+    body::Bytes,
+    extract::{rejection::QueryRejection, Extension, Path, Query},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, Router},
+    Json,
 };
-use std::{collections::HashMap, net::SocketAddr};
+use profile_core::{
+    chart::{Chart, DEFAULT_SIZE},
+    json::JsonProfile,
+    model::DecentProfile,
+    parser::Step,
+    profile::analyze,
+    shot::ShotSeries,
+    writer::write_profile,
+};
+use serde::{Deserialize, Serialize};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tower_http::cors::CorsLayer;
+
+mod chart;
+mod error;
+mod visualizer;
+
+use error::ApiError;
+use visualizer::VisualizerShot;
+
+struct Visualizer {
+    client: reqwest::Client,
+    endpoint: String,
+}
 
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
 
+    let endpoint =
+        std::env::var("VISUALIZER_ENDPOINT").unwrap_or_else(|_| "http://localhost:18080".into());
+    let timeout = std::env::var("VISUALIZER_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10);
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(timeout))
+        .timeout(Duration::from_secs(timeout))
+        .build()
+        .expect("should build HTTP client");
+    let visualizer = Arc::new(Visualizer { client, endpoint });
+    let rasterizer = Arc::new(chart::Rasterizer::new());
+
     let app = Router::new()
         .route("/ping", get(ping))
-        .route("/profiles/:id", get(profile));
+        .route("/profiles/:id", get(profile))
+        .route("/profiles/:id/chart.svg", get(chart_svg))
+        .route("/profiles/:id/chart.png", get(chart_png))
+        .route("/shots/:id", get(shot))
+        .layer(Extension(visualizer))
+        .layer(Extension(rasterizer))
+        // NOTE: The viewer is served from another origin and fetches profiles from the browser
+        .layer(CorsLayer::permissive());
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     tracing::debug!("Listening on {}", addr);
@@ -25,17 +73,139 @@ async fn ping() -> &'static str {
     "pong"
 }
 
-async fn profile(Path(id): Path<String>, Query(params): Query<HashMap<String, String>>) -> String {
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    Tcl,
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileParams {
+    format: Option<Format>,
+}
+
+#[derive(Serialize)]
+struct ProfileBody {
+    profile: JsonProfile,
+    warnings: Vec<String>,
+}
+
+async fn profile(
+    Path(id): Path<String>,
+    params: Result<Query<ProfileParams>, QueryRejection>,
+    Extension(visualizer): Extension<Arc<Visualizer>>,
+) -> Result<Response, ApiError> {
+    let Query(params) = params?;
     tracing::debug!("params={:?}", params);
 
-    let endpoint =
-        std::env::var("VISUALIZER_ENDPOINT").unwrap_or_else(|_| "http://localhost:18080".into());
-    let url = format!("{}/api/shots/{}/profile", endpoint, id);
+    let (profile, _) = fetch_profile(&visualizer, id).await?;
+    let res = match params.format.unwrap_or(Format::Json) {
+        Format::Tcl => write_profile(&profile.to_commands()).into_response(),
+        Format::Json => Json(ProfileBody {
+            profile: JsonProfile::from(&profile),
+            warnings: profile.warnings().iter().map(|w| w.to_string()).collect(),
+        })
+        .into_response(),
+    };
+    Ok(res)
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartParams {
+    width: Option<f64>,
+    height: Option<f64>,
+}
+
+impl ChartParams {
+    fn size(&self) -> Result<(f64, f64), ApiError> {
+        let size = (
+            self.width.unwrap_or(DEFAULT_SIZE.0),
+            self.height.unwrap_or(DEFAULT_SIZE.1),
+        );
+        let valid = |v: f64| (MIN_CHART_SIZE..=MAX_CHART_SIZE).contains(&v);
+        if !valid(size.0) || !valid(size.1) {
+            return Err(ApiError::InvalidParams(format!(
+                "width and height must be between {} and {}",
+                MIN_CHART_SIZE, MAX_CHART_SIZE
+            )));
+        }
+        Ok(size)
+    }
+}
+
+const MIN_CHART_SIZE: f64 = 100.;
+const MAX_CHART_SIZE: f64 = 4096.;
+
+async fn chart_svg(
+    Path(id): Path<String>,
+    params: Result<Query<ChartParams>, QueryRejection>,
+    Extension(visualizer): Extension<Arc<Visualizer>>,
+) -> Result<Response, ApiError> {
+    let Query(params) = params?;
+    let size = params.size()?;
+    let (profile, steps) = fetch_profile(&visualizer, id).await?;
+    let analyzed = analyze(&steps).with_tank_temperature(profile.temperature.tank);
+    let svg = Chart::new(&analyzed, size).to_svg();
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+}
+
+async fn chart_png(
+    Path(id): Path<String>,
+    params: Result<Query<ChartParams>, QueryRejection>,
+    Extension(visualizer): Extension<Arc<Visualizer>>,
+    Extension(rasterizer): Extension<Arc<chart::Rasterizer>>,
+) -> Result<Response, ApiError> {
+    let Query(params) = params?;
+    let size = params.size()?;
+    let (profile, steps) = fetch_profile(&visualizer, id).await?;
+    let analyzed = analyze(&steps).with_tank_temperature(profile.temperature.tank);
+    let chart = Chart::new(&analyzed, size);
+    let png = tokio::task::spawn_blocking(move || rasterizer.png(&chart))
+        .await
+        .map_err(|e| ApiError::Render(e.to_string()))?
+        .map_err(ApiError::Render)?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+/// Measured curves of a shot, to be drawn over its profile from `/profiles/:id`.
+async fn shot(
+    Path(id): Path<String>,
+    Extension(visualizer): Extension<Arc<Visualizer>>,
+) -> Result<Json<ShotSeries>, ApiError> {
+    let content = fetch(&visualizer, id, "download").await?;
+    let shot = serde_json::from_slice::<VisualizerShot>(&content)
+        .map_err(|e| ApiError::InvalidShot(e.to_string()))?;
+    Ok(Json(shot.series().map_err(ApiError::InvalidShot)?))
+}
+
+/// Fetches a resource of a shot, e.g. `profile`, from visualizer.
+async fn fetch(visualizer: &Visualizer, id: String, resource: &str) -> Result<Bytes, ApiError> {
+    let url = format!("{}/api/shots/{}/{}", visualizer.endpoint, id, resource);
     tracing::debug!("url={}", url);
 
-    let res = reqwest::get(url).await;
-    match res {
-        Ok(res) => res.text().await.unwrap(),
-        Err(e) => panic!("ERROR: {:?}", e),
+    let res = visualizer.client.get(url).send().await?;
+    match res.status() {
+        StatusCode::NOT_FOUND => Err(ApiError::ShotNotFound(id)),
+        status if !status.is_success() => Err(ApiError::UpstreamUnavailable(format!(
+            "unexpected status {}",
+            status
+        ))),
+        _ => Ok(res.bytes().await?),
+    }
+}
+
+/// Fetches a shot's profile from visualizer and checks it is one the viewer can draw.
+async fn fetch_profile(
+    visualizer: &Visualizer,
+    id: String,
+) -> Result<(DecentProfile, Vec<Step>), ApiError> {
+    let content = fetch(visualizer, id, "profile").await?;
+    let profile = DecentProfile::parse(&content)?;
+    match profile.shot_steps() {
+        Some(steps) => Ok((profile, steps)),
+        None => Err(ApiError::InvalidProfile(
+            "missing or unsupported settings_profile_type".into(),
+        )),
     }
 }
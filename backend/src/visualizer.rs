@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use profile_core::shot::ShotSeries;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Shot from visualizer's `/api/shots/:id/download`, only the parts the viewer draws.
+#[derive(Debug, Deserialize)]
+pub struct VisualizerShot {
+    timeframe: Vec<Value>,
+    #[serde(default)]
+    data: HashMap<String, Vec<Value>>,
+}
+
+impl VisualizerShot {
+    /// Curves as numbers, visualizer keeps them as the strings the DE1 app uploaded.
+    pub fn series(&self) -> Result<ShotSeries, String> {
+        // NOTE: Curves are missing e.g. for shots pulled without a scale
+        let curve = |key: &str| match self.data.get(key) {
+            Some(values) => numbers(key, values),
+            None => Ok(vec![]),
+        };
+        Ok(ShotSeries {
+            elapsed: numbers("timeframe", &self.timeframe)?,
+            pressure: curve("espresso_pressure")?,
+            flow: curve("espresso_flow")?,
+            weight: curve("espresso_weight")?,
+            temperature: curve("espresso_temperature_basket")?,
+        })
+    }
+}
+
+fn numbers(key: &str, values: &[Value]) -> Result<Vec<f64>, String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let number = match v {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => s.trim().parse().ok(),
+                _ => None,
+            };
+            number.ok_or_else(|| format!("`{}` has a value that is not a number at {}", key, i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series() {
+        let shot: VisualizerShot = serde_json::from_str(
+            r#"{
+                "timeframe": ["0.0", "0.25", 0.5],
+                "data": {
+                    "espresso_pressure": ["0.0", "1.5", "3.25"],
+                    "espresso_flow": [" 0.1", "2", "4.0"],
+                    "espresso_temperature_basket": ["88.0", "88.5", "89.0"],
+                    "espresso_state_change": ["0", "1", "1"]
+                },
+                "profile_title": "Simple pour"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            shot.series(),
+            Ok(ShotSeries {
+                elapsed: vec![0., 0.25, 0.5],
+                pressure: vec![0., 1.5, 3.25],
+                flow: vec![0.1, 2., 4.],
+                weight: vec![],
+                temperature: vec![88., 88.5, 89.],
+            })
+        );
+    }
+
+    #[test]
+    fn test_series_invalid() {
+        let shot: VisualizerShot =
+            serde_json::from_str(r#"{"timeframe": ["0.0"], "data": {"espresso_flow": [null]}}"#)
+                .unwrap();
+        assert_eq!(
+            shot.series(),
+            Err("`espresso_flow` has a value that is not a number at 0".into())
+        );
+    }
+}
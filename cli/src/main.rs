@@ -0,0 +1,306 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::{ArgEnum, Parser, Subcommand};
+use profile_core::chart::{Chart, DEFAULT_SIZE};
+use profile_core::model::{DecentProfile, StepSpec};
+use profile_core::profile::analyze;
+use profile_core::{json, writer};
+
+/// Inspect, validate and convert Decent Espresso profile files.
+#[derive(Parser)]
+#[clap(name = "dpv", version)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parse files and report errors and warnings
+    Validate {
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+        /// Fail when a file has warnings too
+        #[clap(long)]
+        deny_warnings: bool,
+    },
+    /// Print the profile summary and its steps
+    Show { file: PathBuf },
+    /// Convert a profile between Tcl and JSON
+    Convert {
+        file: PathBuf,
+        #[clap(long, arg_enum)]
+        to: Format,
+        /// Write to a file instead of stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Draw the target curves of a profile
+    Render {
+        file: PathBuf,
+        #[clap(long)]
+        svg: PathBuf,
+        #[clap(long, default_value_t = DEFAULT_SIZE.0)]
+        width: f64,
+        #[clap(long, default_value_t = DEFAULT_SIZE.1)]
+        height: f64,
+    },
+}
+
+#[derive(Clone, Copy, ArgEnum)]
+enum Format {
+    Tcl,
+    Json,
+}
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Validate {
+            files,
+            deny_warnings,
+        } => validate(&files, deny_warnings),
+        Command::Show { file } => show(&file),
+        Command::Convert { file, to, output } => convert(&file, to, output.as_deref()),
+        Command::Render {
+            file,
+            svg,
+            width,
+            height,
+        } => render(&file, &svg, (width, height)),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn load(path: &Path) -> Result<DecentProfile> {
+    let content = fs::read(path)?;
+    let profile = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => json::profile(&String::from_utf8_lossy(&content))?,
+        _ => DecentProfile::parse(&content)?,
+    };
+    Ok(profile)
+}
+
+fn validate(files: &[PathBuf], deny_warnings: bool) -> Result<()> {
+    let mut failed = 0;
+    for path in files {
+        match load(path) {
+            Ok(profile) => {
+                let mut warnings = profile
+                    .warnings()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                if profile.shot_steps().is_none() {
+                    warnings.push("unsupported profile type".into());
+                }
+                for w in warnings.iter() {
+                    println!("{}: warning: {}", path.display(), w);
+                }
+                if deny_warnings && !warnings.is_empty() {
+                    failed += 1;
+                }
+            }
+            Err(e) => {
+                println!("{}: error: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} files failed", failed, files.len()).into());
+    }
+    Ok(())
+}
+
+fn show(path: &Path) -> Result<()> {
+    let profile = load(path)?;
+    let field = |v: Option<String>| v.unwrap_or_else(|| "-".into());
+    println!("Title:    {}", field(profile.title.clone()));
+    println!("Author:   {}", field(profile.author.clone()));
+    println!(
+        "Type:     {}",
+        field(profile.profile_type.map(|v| v.to_string()))
+    );
+    println!(
+        "Beverage: {}",
+        field(profile.beverage_type.map(|v| v.to_string()))
+    );
+
+    let steps = profile.shot_steps().unwrap_or_default();
+    let analyzed = analyze(&steps);
+    println!("Duration: {}s", analyzed.elapsed_time);
+    println!();
+    print!("{}", steps_table(&steps));
+    Ok(())
+}
+
+fn steps_table(steps: &[profile_core::parser::Step]) -> String {
+    let mut rows = vec![[
+        "#",
+        "name",
+        "pump",
+        "transition",
+        "pressure",
+        "flow",
+        "temp",
+        "seconds",
+        "exit",
+        "limiter",
+    ]
+    .map(String::from)];
+    for (i, step) in steps.iter().enumerate() {
+        let spec = StepSpec::from(step);
+        let number = |v: f32| format!("{}", v);
+        rows.push([
+            (i + 1).to_string(),
+            spec.name.clone(),
+            spec.pump.to_string(),
+            spec.transition.to_string(),
+            number(spec.pressure),
+            number(spec.flow),
+            spec.temperature.map(number).unwrap_or_default(),
+            number(spec.seconds),
+            spec.exit
+                .map(|e| format!("{} {}", e.kind, e.value))
+                .unwrap_or_default(),
+            spec.limiter
+                .map(|l| format!("{} ±{}", l.value, l.range))
+                .unwrap_or_default(),
+        ]);
+    }
+
+    let mut widths = [0; 10];
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for row in rows.iter() {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<1$}", cell, width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn convert(path: &Path, to: Format, output: Option<&Path>) -> Result<()> {
+    let profile = load(path)?;
+    let text = match to {
+        Format::Tcl => writer::write_profile(&profile.to_commands()),
+        Format::Json => json::write_profile(&profile) + "\n",
+    };
+    match output {
+        Some(output) => fs::write(output, text)?,
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn render(path: &Path, svg: &Path, size: (f64, f64)) -> Result<()> {
+    let profile = load(path)?;
+    let steps = profile
+        .shot_steps()
+        .ok_or("unsupported profile type, nothing to render")?;
+    let analyzed = analyze(&steps).with_tank_temperature(profile.temperature.tank);
+    fs::write(svg, Chart::new(&analyzed, size).to_svg())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use profile_core::parser::steps;
+
+    #[test]
+    fn test_steps_table() {
+        let steps = steps(
+            b"{name fill pump flow flow 4 seconds 10 exit_if 1 exit_type pressure_over \
+              exit_pressure_over 3} {name pour pressure 9 seconds 20 temperature 93}",
+        )
+        .unwrap();
+        assert_eq!(
+            steps_table(&steps),
+            "\
+#  name  pump      transition  pressure  flow  temp  seconds  exit             limiter
+1  fill  flow      fast        0         4           10       pressure_over 3
+2  pour  pressure  fast        9         0     93    20
+"
+        );
+    }
+
+    #[test]
+    fn test_convert_tcl_round_trip() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../web/profiles/7g basket.tcl");
+        let output = std::env::temp_dir().join("dpv-test-convert-7g-basket.tcl");
+        convert(&path, Format::Tcl, Some(&output)).unwrap();
+        // NOTE: Blank lines at the end of the file are the only thing not kept
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            format!("{}\n", content.trim_end())
+        );
+    }
+
+    #[test]
+    fn test_render_negative_seconds() {
+        let dir = std::env::temp_dir().join("dpv-test-render-negative-seconds");
+        fs::create_dir_all(&dir).unwrap();
+        let tcl = dir.join("profile.tcl");
+        let json = dir.join("profile.json");
+        let svg = dir.join("chart.svg");
+        fs::write(
+            &tcl,
+            "settings_profile_type settings_2c\nadvanced_shot {{name a pressure 9 seconds -5}}\n",
+        )
+        .unwrap();
+        fs::write(
+            &json,
+            r#"{"version": "2", "legacy_profile_type": "settings_2c",
+                "steps": [{"name": "a", "pressure": 9, "seconds": -5}]}"#,
+        )
+        .unwrap();
+
+        for path in [&tcl, &json] {
+            render(path, &svg, DEFAULT_SIZE).unwrap();
+            assert!(fs::read_to_string(&svg).unwrap().starts_with("<svg"));
+        }
+    }
+
+    #[test]
+    fn test_validate_deny_unsupported_type() {
+        let path = std::env::temp_dir().join("dpv-test-validate-settings-1.tcl");
+        fs::write(
+            &path,
+            "settings_profile_type settings_1\nprofile_title {Lever}\n",
+        )
+        .unwrap();
+        assert!(load(&path).unwrap().warnings().is_empty());
+        let files = vec![path];
+        assert!(validate(&files, false).is_ok());
+        assert!(validate(&files, true).is_err());
+    }
+
+    #[test]
+    fn test_load_by_extension() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../profile-core/fixtures");
+        let tcl = load(&Path::new(dir).join("profile.tcl")).unwrap();
+        let json = load(&Path::new(dir).join("profile.json")).unwrap();
+        assert_eq!(tcl.title.as_deref(), Some("Filter 2.1"));
+        assert_eq!(json.title.as_deref(), Some("Blooming Espresso"));
+    }
+}
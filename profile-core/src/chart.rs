@@ -0,0 +1,694 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::parser::SensorType;
+use crate::profile::{AnalyzedProfile, ExitKind, LimitSegment, PositionList, TemperatureSegment};
+use crate::scale;
+use crate::shot::ShotSeries;
+
+pub const DEFAULT_SIZE: (f64, f64) = (1024., 480.);
+
+const TEMPERATURE_DOMAIN: (f64, f64) = (20., 100.);
+const PRESSURE_OR_FLOW_DOMAIN: (f64, f64) = (0., 12.);
+/// Rough distance in px between two ticks on an axis.
+const TICK_SPACING: f64 = 60.;
+const STEP_NAME_SIZE: f64 = 10.;
+
+/// Drawing of an analyzed profile, shared by the viewer and the headless SVG output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chart {
+    pub width: f64,
+    pub height: f64,
+    /// Plot area inside the axes as left, top, right and bottom.
+    pub area: (f64, f64, f64, f64),
+    pub elapsed_time: f64,
+    pub shapes: Vec<Shape>,
+    /// Part of `shapes` drawing the axes with their ticks, grid and labels.
+    pub axes: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        stroke: &'static str,
+        stroke_width: f64,
+        dash: Option<&'static str>,
+    },
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        fill: &'static str,
+        opacity: f64,
+    },
+    Text {
+        x: f64,
+        y: f64,
+        text: String,
+        /// `text-anchor` of the label, i.e. `start`, `middle` or `end`.
+        anchor: &'static str,
+        fill: &'static str,
+        font_size: f64,
+    },
+    Polyline {
+        points: Vec<(f64, f64)>,
+        stroke: &'static str,
+        stroke_width: f64,
+    },
+}
+
+impl Chart {
+    pub fn new(data: &AnalyzedProfile, size: (f64, f64)) -> Self {
+        let (width, height) = size;
+        let inner = (40., 24., width - 40., height - 36.);
+        let mut chart = Chart {
+            width,
+            height,
+            area: inner,
+            elapsed_time: data.elapsed_time,
+            shapes: vec![],
+            axes: 0..0,
+        };
+
+        chart.step_bands(data, inner);
+        let start = chart.shapes.len();
+        chart.axis(data, inner);
+        chart.axes = start..chart.shapes.len();
+
+        let x = scale((0., data.elapsed_time), (inner.0, inner.2));
+        let t = scale(TEMPERATURE_DOMAIN, (inner.3, inner.1));
+        chart.temperature(&data.temperature, &x, &t);
+        if let Some(tank) = data.tank_temperature {
+            chart.shapes.push(Shape::Line {
+                x1: x(0.),
+                y1: t(tank),
+                x2: x(data.elapsed_time),
+                y2: t(tank),
+                stroke: "indianred",
+                stroke_width: 1.,
+                dash: Some("1 4"),
+            });
+        }
+        let y = scale(PRESSURE_OR_FLOW_DOMAIN, (inner.3, inner.1));
+        // NOTE: Bands go first so the target curves stay on top
+        chart.limits(&data.pressure_limits, &x, &y, "darkgreen");
+        chart.limits(&data.flow_limits, &x, &y, "darkblue");
+        chart.series(&data.pressure, &x, &y, "darkgreen");
+        chart.series(&data.flow, &x, &y, "darkblue");
+        chart.exits(data, &x, &y);
+        if let Some(shot) = &data.shot {
+            chart.shot(shot, &x, &y, &t);
+        }
+
+        chart
+    }
+
+    /// Alternating background per step with its name at the top.
+    fn step_bands(&mut self, data: &AnalyzedProfile, inner: (f64, f64, f64, f64)) {
+        let x = scale((0., data.elapsed_time), (inner.0, inner.2));
+        for (i, step) in data.steps.iter().enumerate() {
+            let width = x(step.end) - x(step.start);
+            if i % 2 == 0 {
+                self.shapes.push(Shape::Rect {
+                    x: x(step.start),
+                    y: inner.1,
+                    width,
+                    height: inner.3 - inner.1,
+                    fill: "gray",
+                    opacity: 0.08,
+                });
+            }
+            let name = match step.name.as_str() {
+                "" => format!("Step {}", i + 1),
+                name => name.to_string(),
+            };
+            if let Some(name) = truncate(&name, width - 8., STEP_NAME_SIZE) {
+                self.shapes.push(Shape::Text {
+                    x: x(step.start) + 4.,
+                    y: inner.1 + STEP_NAME_SIZE + 2.,
+                    text: name,
+                    anchor: "start",
+                    fill: "gray",
+                    font_size: STEP_NAME_SIZE,
+                });
+            }
+        }
+    }
+
+    fn axis(&mut self, data: &AnalyzedProfile, inner: (f64, f64, f64, f64)) {
+        let line = |x1, y1, x2, y2, stroke, stroke_width| Shape::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            stroke,
+            stroke_width,
+            dash: None,
+        };
+        let label = |x, y, text: String, anchor| Shape::Text {
+            x,
+            y,
+            text,
+            anchor,
+            fill: "dimgray",
+            font_size: 11.,
+        };
+
+        let x = scale((0., data.elapsed_time), (inner.0, inner.2));
+        let x_ticks = nice_ticks((0., data.elapsed_time), (inner.2 - inner.0) / TICK_SPACING);
+        let y = scale(PRESSURE_OR_FLOW_DOMAIN, (inner.3, inner.1));
+        let y_ticks = nice_ticks(PRESSURE_OR_FLOW_DOMAIN, (inner.3 - inner.1) / TICK_SPACING);
+        let t = scale(TEMPERATURE_DOMAIN, (inner.3, inner.1));
+        let t_ticks = nice_ticks(TEMPERATURE_DOMAIN, (inner.3 - inner.1) / TICK_SPACING);
+
+        // NOTE: Grid follows the left axis, the temperature ticks would double the lines
+        for v in x_ticks.iter().filter(|v| **v > 0.) {
+            self.shapes
+                .push(line(x(*v), inner.3, x(*v), inner.1, "gainsboro", 0.5));
+        }
+        for v in y_ticks.iter().filter(|v| **v > PRESSURE_OR_FLOW_DOMAIN.0) {
+            self.shapes
+                .push(line(inner.0, y(*v), inner.2, y(*v), "gainsboro", 0.5));
+        }
+
+        self.shapes
+            .push(line(inner.0, inner.3, inner.2, inner.3, "darkgray", 1.25));
+        self.shapes
+            .push(line(inner.0, inner.3, inner.0, inner.1, "darkgray", 1.25));
+        self.shapes
+            .push(line(inner.2, inner.3, inner.2, inner.1, "darkgray", 1.25));
+
+        for v in x_ticks.iter() {
+            self.shapes
+                .push(line(x(*v), inner.3, x(*v), inner.3 + 5., "darkgray", 0.75));
+            self.shapes
+                .push(label(x(*v), inner.3 + 17., v.to_string(), "middle"));
+        }
+        for v in y_ticks.iter() {
+            self.shapes
+                .push(line(inner.0 - 5., y(*v), inner.0, y(*v), "darkgray", 0.75));
+            self.shapes
+                .push(label(inner.0 - 8., y(*v) + 4., v.to_string(), "end"));
+        }
+        for v in t_ticks.iter() {
+            self.shapes
+                .push(line(inner.2, t(*v), inner.2 + 5., t(*v), "darkgray", 0.75));
+            self.shapes
+                .push(label(inner.2 + 8., t(*v) + 4., v.to_string(), "start"));
+        }
+
+        self.shapes.push(label(
+            (inner.0 + inner.2) / 2.,
+            self.height - 4.,
+            "Time (s)".into(),
+            "middle",
+        ));
+        self.shapes.push(label(
+            inner.0,
+            inner.1 - 8.,
+            "Pressure (bar) / Flow (ml/s)".into(),
+            "start",
+        ));
+        self.shapes.push(label(
+            inner.2,
+            inner.1 - 8.,
+            "Temperature (°C)".into(),
+            "end",
+        ));
+    }
+
+    fn series(
+        &mut self,
+        positions: &PositionList,
+        x: impl Fn(f64) -> f64,
+        y: impl Fn(f64) -> f64,
+        stroke: &'static str,
+    ) {
+        for (x1, y1, x2, y2) in positions.iter() {
+            self.shapes.push(Shape::Line {
+                x1: x(*x1),
+                y1: y(*y1),
+                x2: x(*x2),
+                y2: y(*y2),
+                stroke,
+                stroke_width: 1.5,
+                dash: None,
+            });
+        }
+    }
+
+    /// Like `series`, with water-sensor targets dashed to tell them from coffee ones.
+    fn temperature(
+        &mut self,
+        segments: &[TemperatureSegment],
+        x: impl Fn(f64) -> f64,
+        y: impl Fn(f64) -> f64,
+    ) {
+        for segment in segments.iter() {
+            let (x1, y1, x2, y2) = segment.position;
+            self.shapes.push(Shape::Line {
+                x1: x(x1),
+                y1: y(y1),
+                x2: x(x2),
+                y2: y(y2),
+                stroke: "darkred",
+                stroke_width: 1.5,
+                dash: match segment.sensor {
+                    SensorType::Coffee => None,
+                    SensorType::Water => Some("6 3"),
+                },
+            });
+        }
+    }
+
+    /// Shaded bands from `value - range` up to the limit, where the firmware eases in.
+    fn limits(
+        &mut self,
+        segments: &[LimitSegment],
+        x: impl Fn(f64) -> f64,
+        y: impl Fn(f64) -> f64,
+        fill: &'static str,
+    ) {
+        for segment in segments.iter() {
+            let top = y(segment.value);
+            let bottom = y((segment.value - segment.range).max(0.));
+            self.shapes.push(Shape::Rect {
+                x: x(segment.start),
+                y: top,
+                width: x(segment.end) - x(segment.start),
+                height: bottom - top,
+                fill,
+                opacity: 0.15,
+            });
+        }
+    }
+
+    /// Dashed markers at the pressure and flow thresholds that end a step.
+    fn exits(&mut self, data: &AnalyzedProfile, x: impl Fn(f64) -> f64, y: impl Fn(f64) -> f64) {
+        for step in data.steps.iter() {
+            for exit in step.exits.iter() {
+                let stroke = match exit.kind {
+                    ExitKind::PressureOver | ExitKind::PressureUnder => "darkgreen",
+                    ExitKind::FlowOver | ExitKind::FlowUnder => "darkblue",
+                    // NOTE: Weight and volume have no axis on the chart
+                    ExitKind::Weight | ExitKind::Volume => continue,
+                };
+                self.shapes.push(Shape::Line {
+                    x1: x(step.start),
+                    y1: y(exit.value),
+                    x2: x(step.end),
+                    y2: y(exit.value),
+                    stroke,
+                    stroke_width: 1.,
+                    dash: Some("4 3"),
+                });
+            }
+        }
+    }
+
+    /// Measured curves in lighter colors on top of the targets of the same quantity.
+    fn shot(
+        &mut self,
+        shot: &ShotSeries,
+        x: impl Fn(f64) -> f64,
+        y: impl Fn(f64) -> f64,
+        t: impl Fn(f64) -> f64,
+    ) {
+        // NOTE: Weight has no axis on the chart, the tooltip shows it instead
+        self.curve(shot, &shot.temperature, &x, &t, "tomato");
+        self.curve(shot, &shot.pressure, &x, &y, "limegreen");
+        self.curve(shot, &shot.flow, &x, &y, "dodgerblue");
+    }
+
+    fn curve(
+        &mut self,
+        shot: &ShotSeries,
+        values: &[f64],
+        x: impl Fn(f64) -> f64,
+        y: impl Fn(f64) -> f64,
+        stroke: &'static str,
+    ) {
+        let points = shot
+            .points(values)
+            .map(|(time, v)| (x(time), y(v)))
+            .collect::<Vec<_>>();
+        if points.len() > 1 {
+            self.shapes.push(Shape::Polyline {
+                points,
+                stroke,
+                stroke_width: 2.,
+            });
+        }
+    }
+
+    /// Time under the horizontal position `x`, `None` outside the plot area.
+    pub fn time_at(&self, x: f64) -> Option<f64> {
+        let (left, _, right, _) = self.area;
+        if x < left || right < x || self.elapsed_time <= 0. {
+            return None;
+        }
+        Some(scale((left, right), (0., self.elapsed_time))(x))
+    }
+
+    pub fn x_at(&self, time: f64) -> f64 {
+        scale((0., self.elapsed_time), (self.area.0, self.area.2))(time)
+    }
+
+    /// Standalone SVG document, e.g. for thumbnails and reports.
+    pub fn to_svg(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Shortens `text` with an ellipsis to fit `width`, `None` when not even that fits.
+fn truncate(text: &str, width: f64, font_size: f64) -> Option<String> {
+    // NOTE: Average glyph width of sans-serif fonts, the exact one isn't known without a renderer
+    let max = (width / (font_size * 0.6)).floor().max(0.) as usize;
+    let count = text.chars().count();
+    if count <= max {
+        return Some(text.to_string());
+    }
+    if max < 2 {
+        return None;
+    }
+    Some(text.chars().take(max - 1).chain(Some('…')).collect())
+}
+
+/// Ticks at multiples of 1, 2 or 5 times a power of ten, about `count` of them.
+fn nice_ticks(domain: (f64, f64), count: f64) -> Vec<f64> {
+    let span = domain.1 - domain.0;
+    if span <= 0. {
+        return vec![domain.0];
+    }
+    let raw = span / count.floor().max(2.);
+    let magnitude = 10f64.powf(raw.log10().floor());
+    // NOTE: Rounds to the closest nice step on a log scale, like d3's `ticks`
+    let error = raw / magnitude;
+    let step = magnitude
+        * if error >= 50f64.sqrt() {
+            10.
+        } else if error >= 10f64.sqrt() {
+            5.
+        } else if error >= 2f64.sqrt() {
+            2.
+        } else {
+            1.
+        };
+
+    // NOTE: Tolerance keeps the last tick when the domain ends on it but division rounds below
+    let first = (domain.0 / step - 1e-9).ceil() as i64;
+    let last = (domain.1 / step + 1e-9).floor() as i64;
+    if step < 1. {
+        // NOTE: Dividing by the inverse keeps e.g. `3 * 0.2` from becoming `0.6000000000000001`
+        let inverse = (1. / step).round();
+        return (first..=last).map(|i| i as f64 / inverse).collect();
+    }
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+impl fmt::Display for Chart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = self.width,
+            h = self.height
+        )?;
+        for shape in self.shapes.iter() {
+            writeln!(f, "{}", shape)?;
+        }
+        f.write_str("</svg>\n")
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                stroke,
+                stroke_width,
+                dash,
+            } => {
+                write!(
+                    f,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}px" stroke-linecap="round""#,
+                    x1, y1, x2, y2, stroke, stroke_width
+                )?;
+                if let Some(dash) = dash {
+                    write!(f, r#" stroke-dasharray="{}""#, dash)?;
+                }
+                f.write_str("/>")
+            }
+            Shape::Rect {
+                x,
+                y,
+                width,
+                height,
+                fill,
+                opacity,
+            } => write!(
+                f,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="{}"/>"#,
+                x, y, width, height, fill, opacity
+            ),
+            Shape::Text {
+                x,
+                y,
+                text,
+                anchor,
+                fill,
+                font_size,
+            } => write!(
+                f,
+                r#"<text x="{}" y="{}" text-anchor="{}" fill="{}" font-size="{}px" font-family="sans-serif">{}</text>"#,
+                x,
+                y,
+                anchor,
+                fill,
+                font_size,
+                escape(text)
+            ),
+            Shape::Polyline {
+                points,
+                stroke,
+                stroke_width,
+            } => write!(
+                f,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}px" stroke-linejoin="round"/>"#,
+                polyline_points(points),
+                stroke,
+                stroke_width
+            ),
+        }
+    }
+}
+
+/// `points` attribute of a polyline, e.g. `0,10 5,12`.
+pub fn polyline_points(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{StepExit, StepInfo};
+
+    fn data() -> AnalyzedProfile {
+        AnalyzedProfile {
+            pressure: vec![(0., 0., 0., 6.), (0., 6., 25., 6.)],
+            steps: vec![StepInfo {
+                name: "pour".into(),
+                start: 0.,
+                end: 25.,
+                exits: vec![StepExit {
+                    kind: ExitKind::PressureOver,
+                    value: 9.,
+                }],
+                exits_early: false,
+            }],
+            elapsed_time: 25.,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(nice_ticks((0., 25.), 3.3), vec![0., 10., 20.]);
+        assert_eq!(
+            nice_ticks((0., 12.), 7.),
+            vec![0., 2., 4., 6., 8., 10., 12.]
+        );
+        assert_eq!(nice_ticks((20., 100.), 4.), vec![20., 40., 60., 80., 100.]);
+        assert_eq!(nice_ticks((0., 0.9), 3.), vec![0., 0.2, 0.4, 0.6, 0.8]);
+        assert_eq!(nice_ticks((0., 0.), 3.), vec![0.]);
+    }
+
+    #[test]
+    fn test_chart_axis() {
+        let chart = Chart::new(&data(), (280., 150.));
+        let labels = chart
+            .shapes
+            .iter()
+            .filter_map(|shape| match shape {
+                Shape::Text { text, anchor, .. } => Some((text.as_str(), *anchor)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                ("pour", "start"),
+                ("0", "middle"),
+                ("10", "middle"),
+                ("20", "middle"),
+                ("0", "end"),
+                ("5", "end"),
+                ("10", "end"),
+                ("50", "start"),
+                ("100", "start"),
+                ("Time (s)", "middle"),
+                ("Pressure (bar) / Flow (ml/s)", "start"),
+                ("Temperature (°C)", "end"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("pour", 24., 10.), Some("pour".into()));
+        assert_eq!(truncate("preinfusion", 30., 10.), Some("prei…".into()));
+        assert_eq!(truncate("preinfusion", 6., 10.), None);
+    }
+
+    #[test]
+    fn test_chart_axes() {
+        let chart = Chart::new(&data(), (280., 150.));
+        let axes = &chart.shapes[chart.axes.clone()];
+        assert!(axes
+            .iter()
+            .any(|shape| matches!(shape, Shape::Text { text, .. } if text == "Time (s)")));
+        assert!(!axes
+            .iter()
+            .any(|shape| matches!(shape, Shape::Text { text, .. } if text == "pour")));
+    }
+
+    #[test]
+    fn test_chart_step_bands() {
+        let svg = Chart::new(&data(), (280., 150.)).to_svg();
+        assert!(svg.contains(
+            r#"<rect x="40" y="24" width="200" height="90" fill="gray" fill-opacity="0.08"/>"#
+        ));
+        assert!(svg.contains(
+            r#"<text x="44" y="36" text-anchor="start" fill="gray" font-size="10px" font-family="sans-serif">pour</text>"#
+        ));
+    }
+
+    #[test]
+    fn test_chart_time_at() {
+        let chart = Chart::new(&data(), (280., 150.));
+        assert_eq!(chart.time_at(40.), Some(0.));
+        assert_eq!(chart.time_at(120.), Some(10.));
+        assert_eq!(chart.time_at(241.), None);
+        assert_eq!(chart.x_at(25.), 240.);
+    }
+
+    #[test]
+    fn test_chart_exits() {
+        let svg = Chart::new(&data(), (280., 150.)).to_svg();
+        assert!(svg.contains(
+            r#"<line x1="40" y1="46.5" x2="240" y2="46.5" stroke="darkgreen" stroke-width="1px" stroke-linecap="round" stroke-dasharray="4 3"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_chart_temperature() {
+        let data = AnalyzedProfile {
+            temperature: vec![TemperatureSegment {
+                position: (0., 90., 25., 90.),
+                sensor: SensorType::Water,
+            }],
+            tank_temperature: Some(40.),
+            ..data()
+        };
+        let svg = Chart::new(&data, (280., 150.)).to_svg();
+        assert!(svg.contains(
+            r#"<line x1="40" y1="35.25" x2="240" y2="35.25" stroke="darkred" stroke-width="1.5px" stroke-linecap="round" stroke-dasharray="6 3"/>"#
+        ));
+        assert!(svg.contains(
+            r#"<line x1="40" y1="91.5" x2="240" y2="91.5" stroke="indianred" stroke-width="1px" stroke-linecap="round" stroke-dasharray="1 4"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_chart_limits() {
+        let data = AnalyzedProfile {
+            flow_limits: vec![LimitSegment {
+                start: 0.,
+                end: 25.,
+                value: 6.,
+                range: 1.2,
+            }],
+            ..data()
+        };
+        let svg = Chart::new(&data, (280., 150.)).to_svg();
+        assert!(svg.contains(
+            r#"<rect x="40" y="69" width="200" height="9" fill="darkblue" fill-opacity="0.15"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_chart_shot() {
+        let shot = ShotSeries {
+            elapsed: vec![0., 15., 30.],
+            pressure: vec![0., 6., 3.],
+            flow: vec![2.],
+            temperature: vec![90., 90., 90.],
+            ..Default::default()
+        };
+        let data = data().with_shot(Some(shot));
+        let chart = Chart::new(&data, (280., 150.));
+        assert_eq!(chart.elapsed_time, 30.);
+        let svg = chart.to_svg();
+        assert!(svg.contains(
+            r#"<polyline points="40,114 140,69 240,91.5" fill="none" stroke="limegreen" stroke-width="2px" stroke-linejoin="round"/>"#
+        ));
+        assert!(svg.contains(r#"<polyline points="40,35.25 "#));
+        // NOTE: A single sample is no curve
+        assert!(!svg.contains("dodgerblue"));
+    }
+
+    #[test]
+    fn test_chart_to_svg() {
+        let svg = Chart::new(&data(), (280., 150.)).to_svg();
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="280" height="150" viewBox="0 0 280 150">"#
+        ));
+        assert!(svg.contains(
+            r#"<line x1="40" y1="114" x2="40" y2="69" stroke="darkgreen" stroke-width="1.5px" stroke-linecap="round"/>"#
+        ));
+        assert!(svg.contains(r#"<line x1="40" y1="69" x2="240" y2="69" stroke="darkgreen""#));
+        assert!(svg.contains(
+            r#"<text x="140" y="146" text-anchor="middle" fill="dimgray" font-size="11px" font-family="sans-serif">Time (s)</text>"#
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,144 @@
+//! Text encoding of profile files, which aren't always UTF-8 when they come from older tablets.
+
+use std::borrow::Cow;
+
+use encoding_rs::{Encoding, BIG5, EUC_KR, GBK, SHIFT_JIS, UTF_8, WINDOWS_1252};
+
+/// Profile text and the encoding it was decoded from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decoded<'a> {
+    pub text: Cow<'a, str>,
+    /// `None` when the input already was UTF-8.
+    pub encoding: Option<&'static str>,
+}
+
+/// Decodes `input` to UTF-8, guessing legacy encodings from the BOM and `profile_language`.
+pub fn decode(input: &[u8]) -> Decoded<'_> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(input) {
+        let (text, _) = encoding.decode_without_bom_handling(&input[bom_len..]);
+        return Decoded {
+            text,
+            encoding: Some(encoding.name()).filter(|_| encoding != UTF_8),
+        };
+    }
+    if let Ok(text) = std::str::from_utf8(input) {
+        return Decoded {
+            text: Cow::Borrowed(text),
+            encoding: None,
+        };
+    }
+
+    // NOTE: windows-1252 maps every byte, so it ends the list and decoding never fails
+    let candidates = language(input).map(candidates).unwrap_or_default();
+    let (text, encoding) = candidates
+        .iter()
+        .chain(Some(&WINDOWS_1252))
+        .find_map(|encoding| {
+            encoding
+                .decode_without_bom_handling_and_without_replacement(input)
+                .map(|text| (text, *encoding))
+        })
+        .unwrap_or_else(|| {
+            (
+                WINDOWS_1252.decode_without_bom_handling(input).0,
+                WINDOWS_1252,
+            )
+        });
+    Decoded {
+        text,
+        encoding: Some(encoding.name()),
+    }
+}
+
+/// Legacy encodings tablets used for the language, most common first.
+fn candidates(language: &str) -> Vec<&'static Encoding> {
+    match language.split(['-', '_']).next() {
+        Some("ja") | Some("jp") => vec![SHIFT_JIS],
+        Some("zh") => match language {
+            "zh-hant" | "zh_TW" | "zh-tw" => vec![BIG5, GBK],
+            _ => vec![GBK, BIG5],
+        },
+        Some("ko") | Some("kr") => vec![EUC_KR],
+        _ => vec![],
+    }
+}
+
+/// Value of `profile_language`, found without decoding since it is ASCII.
+fn language(input: &[u8]) -> Option<&str> {
+    const KEY: &[u8] = b"profile_language";
+    // NOTE: Only a key starting a line is a command, the same text in notes or a title isn't
+    let rest = input.split(|c| *c == b'\n').find_map(|line| {
+        let line = &line[line.iter().position(|c| !c.is_ascii_whitespace())?..];
+        line.strip_prefix(KEY)
+            .filter(|rest| matches!(rest.first(), Some(b' ' | b'\t')))
+    })?;
+    let rest = &rest[rest.iter().position(|c| !matches!(c, b' ' | b'\t'))?..];
+    let len = rest
+        .iter()
+        .position(|c| !(c.is_ascii_alphanumeric() || *c == b'-' || *c == b'_'))
+        .unwrap_or(rest.len());
+    std::str::from_utf8(&rest[..len])
+        .ok()
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8() {
+        let decoded = decode("profile_notes {Crème}".as_bytes());
+        assert_eq!(decoded.text, "profile_notes {Crème}");
+        assert_eq!(decoded.encoding, None);
+
+        let decoded = decode(b"\xef\xbb\xbfauthor Decent");
+        assert_eq!(decoded.text, "author Decent");
+        assert_eq!(decoded.encoding, None);
+    }
+
+    #[test]
+    fn test_decode_windows_1252() {
+        let decoded = decode(b"profile_notes {Cr\xe8me \x80 2}\nprofile_language de\n");
+        assert_eq!(
+            decoded.text,
+            "profile_notes {Crème € 2}\nprofile_language de\n"
+        );
+        assert_eq!(decoded.encoding, Some("windows-1252"));
+    }
+
+    #[test]
+    fn test_decode_by_language() {
+        // NOTE: The last byte of `ソ` is a backslash, which breaks a byte-level tokenizer
+        let decoded =
+            decode(b"profile_title {\x83G\x83X\x83v\x83\x8c\x83b\x83\\}\nprofile_language ja\n");
+        assert_eq!(
+            decoded.text,
+            "profile_title {エスプレッソ}\nprofile_language ja\n"
+        );
+        assert_eq!(decoded.encoding, Some("Shift_JIS"));
+
+        let decoded = decode(b"profile_language zh-hans\nprofile_title {\xc5\xa8\xcb\xf5}\n");
+        assert_eq!(
+            decoded.text,
+            "profile_language zh-hans\nprofile_title {浓缩}\n"
+        );
+        assert_eq!(decoded.encoding, Some("GBK"));
+    }
+
+    #[test]
+    fn test_decode_language_in_notes() {
+        let decoded = decode(
+            b"profile_notes {Set profile_language ja for \x83G}\n  profile_language zh-hans\n",
+        );
+        assert_eq!(decoded.encoding, Some("GBK"));
+        assert_eq!(language(b"profile_notes {profile_language ja}\n"), None);
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let decoded = decode(b"\xff\xfea\x00b\x00");
+        assert_eq!(decoded.text, "ab");
+        assert_eq!(decoded.encoding, Some("UTF-16LE"));
+    }
+}
@@ -0,0 +1,468 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de::{Deserializer, Error as _};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{DecentProfile, StepSpec};
+use crate::parser::{
+    BeverageType, ExitType, ProfileType, Prop, PumpType, SensorType, Step, TransitionType,
+    UnexpectedValueError,
+};
+
+/// Profile in the JSON format (`version: "2"`) used by the DE1 app and visualizer.coffee.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct JsonProfile {
+    pub version: String,
+    #[serde(
+        deserialize_with = "enum_value",
+        serialize_with = "display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub legacy_profile_type: Option<ProfileType>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(
+        deserialize_with = "flag",
+        serialize_with = "boolean",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hidden: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(
+        deserialize_with = "enum_value",
+        serialize_with = "display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub beverage_type: Option<BeverageType>,
+    pub steps: Vec<JsonStep>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub tank_temperature: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub target_weight: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub target_volume: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub target_volume_count_start: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct JsonStep {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(
+        deserialize_with = "enum_value",
+        serialize_with = "display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sensor: Option<SensorType>,
+    #[serde(
+        deserialize_with = "enum_value",
+        serialize_with = "display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pump: Option<PumpType>,
+    #[serde(
+        deserialize_with = "enum_value",
+        serialize_with = "display",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transition: Option<TransitionType>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub flow: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    #[serde(with = "number", skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit: Option<JsonExit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limiter: Option<JsonLimiter>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonExit {
+    #[serde(rename = "type")]
+    pub kind: ExitKind,
+    pub condition: ExitCondition,
+    #[serde(with = "required_number")]
+    pub value: f32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitKind {
+    Pressure,
+    Flow,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitCondition {
+    Over,
+    Under,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonLimiter {
+    #[serde(with = "required_number")]
+    pub value: f32,
+    #[serde(with = "required_number")]
+    pub range: f32,
+}
+
+pub fn profile(input: &str) -> Result<DecentProfile, serde_json::Error> {
+    let profile: JsonProfile = serde_json::from_str(input)?;
+    Ok(profile.into())
+}
+
+pub fn write_profile(profile: &DecentProfile) -> String {
+    serde_json::to_string_pretty(&JsonProfile::from(profile)).expect("should be serialized")
+}
+
+impl From<JsonProfile> for DecentProfile {
+    fn from(json: JsonProfile) -> Self {
+        let mut profile = DecentProfile {
+            title: json.title,
+            author: json.author,
+            notes: json.notes,
+            language: json.lang,
+            hidden: json.hidden,
+            beverage_type: json.beverage_type,
+            // NOTE: Steps in JSON are already expanded from the basic settings, so they are
+            // kept as an advanced profile whatever `legacy_profile_type` says
+            profile_type: Some(ProfileType::Settings2C),
+            steps: Some(json.steps.iter().map(Step::from).collect()),
+            ..Default::default()
+        };
+        profile.temperature.tank = json.tank_temperature;
+        profile.target.weight_advanced = json.target_weight;
+        profile.target.volume_advanced = json.target_volume;
+        profile.target.volume_advanced_count_start = json.target_volume_count_start;
+        profile
+    }
+}
+
+impl From<&DecentProfile> for JsonProfile {
+    fn from(profile: &DecentProfile) -> Self {
+        let kind = profile.profile_type.map(|t| {
+            match t {
+                ProfileType::Settings2A => "pressure",
+                ProfileType::Settings2B => "flow",
+                _ => "advanced",
+            }
+            .to_string()
+        });
+        JsonProfile {
+            version: "2".into(),
+            legacy_profile_type: profile.profile_type,
+            kind,
+            lang: profile.language.clone(),
+            hidden: profile.hidden,
+            title: profile.title.clone(),
+            author: profile.author.clone(),
+            notes: profile.notes.clone(),
+            beverage_type: profile.beverage_type,
+            steps: profile
+                .shot_steps()
+                .unwrap_or_default()
+                .iter()
+                .map(JsonStep::from)
+                .collect(),
+            tank_temperature: profile.temperature.tank,
+            target_weight: profile.target.weight_advanced.or(profile.target.weight),
+            target_volume: profile.target.volume_advanced.or(profile.target.volume),
+            target_volume_count_start: profile.target.volume_advanced_count_start,
+        }
+    }
+}
+
+impl From<&JsonStep> for Step {
+    fn from(json: &JsonStep) -> Self {
+        let mut props = vec![];
+        if let Some(v) = &json.name {
+            props.push(Prop::Name(v.clone()));
+        }
+        if let Some(v) = json.temperature {
+            props.push(Prop::Temperature(v.into()));
+        }
+        if let Some(v) = json.sensor {
+            props.push(Prop::Sensor(v));
+        }
+        if let Some(v) = json.pump {
+            props.push(Prop::Pump(v));
+        }
+        if let Some(v) = json.transition {
+            props.push(Prop::Transition(v));
+        }
+        if let Some(v) = json.pressure {
+            props.push(Prop::Pressure(v.into()));
+        }
+        if let Some(v) = json.flow {
+            props.push(Prop::Flow(v.into()));
+        }
+        if let Some(v) = json.seconds {
+            props.push(Prop::Seconds(v.into()));
+        }
+        if let Some(v) = json.volume {
+            props.push(Prop::Volume(v.into()));
+        }
+        if let Some(v) = json.weight {
+            props.push(Prop::Weight(v.into()));
+        }
+        match &json.exit {
+            Some(exit) => {
+                let (kind, prop) = match (exit.kind, exit.condition) {
+                    (ExitKind::Pressure, ExitCondition::Over) => (
+                        ExitType::PressureOver,
+                        Prop::ExitPressureOver(exit.value.into()),
+                    ),
+                    (ExitKind::Pressure, ExitCondition::Under) => (
+                        ExitType::PressureUnder,
+                        Prop::ExitPressureUnder(exit.value.into()),
+                    ),
+                    (ExitKind::Flow, ExitCondition::Over) => {
+                        (ExitType::FlowOver, Prop::ExitFlowOver(exit.value.into()))
+                    }
+                    (ExitKind::Flow, ExitCondition::Under) => {
+                        (ExitType::FlowUnder, Prop::ExitFlowUnder(exit.value.into()))
+                    }
+                };
+                props.push(Prop::ExitIf(true));
+                props.push(Prop::ExitType(kind));
+                props.push(prop);
+            }
+            None => props.push(Prop::ExitIf(false)),
+        }
+        if let Some(limiter) = &json.limiter {
+            props.push(Prop::MaxFlowOrPressure(limiter.value.into()));
+            props.push(Prop::MaxFlowOrPressureRange(limiter.range.into()));
+        }
+        Step(props)
+    }
+}
+
+impl From<&Step> for JsonStep {
+    fn from(step: &Step) -> Self {
+        let spec = StepSpec::from(step);
+        JsonStep {
+            name: step.name().map(String::from),
+            temperature: spec.temperature,
+            sensor: step.sensor(),
+            pump: step.pump(),
+            transition: step.transition(),
+            pressure: step.number("pressure"),
+            flow: step.number("flow"),
+            seconds: step.seconds(),
+            volume: step.number("volume"),
+            weight: step.number("weight"),
+            exit: spec.exit.map(|exit| {
+                let (kind, condition) = match exit.kind {
+                    ExitType::PressureOver => (ExitKind::Pressure, ExitCondition::Over),
+                    ExitType::PressureUnder => (ExitKind::Pressure, ExitCondition::Under),
+                    ExitType::FlowOver => (ExitKind::Flow, ExitCondition::Over),
+                    ExitType::FlowUnder => (ExitKind::Flow, ExitCondition::Under),
+                };
+                JsonExit {
+                    kind,
+                    condition,
+                    value: exit.value,
+                }
+            }),
+            limiter: spec.limiter.map(|limiter| JsonLimiter {
+                value: limiter.value,
+                range: limiter.range,
+            }),
+        }
+    }
+}
+
+/// Numbers are written as strings by the DE1 app but as numbers by some other tools.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Lenient {
+    Number(f32),
+    Text(String),
+    Bool(bool),
+}
+
+mod number {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &Option<f32>, s: S) -> Result<S::Ok, S::Error> {
+        match v {
+            Some(v) => s.serialize_str(&v.to_string()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f32>, D::Error> {
+        match Option::<Lenient>::deserialize(d)? {
+            Some(Lenient::Number(v)) => Ok(Some(v)),
+            Some(Lenient::Text(v)) if v.trim().is_empty() => Ok(None),
+            Some(Lenient::Text(v)) => v
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| D::Error::custom(format!("expected a number, found `{}`", v))),
+            Some(Lenient::Bool(v)) => Err(D::Error::custom(format!(
+                "expected a number, found `{}`",
+                v
+            ))),
+            None => Ok(None),
+        }
+    }
+}
+
+mod required_number {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &f32, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&v.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+        number::deserialize(d)?.ok_or_else(|| D::Error::custom("expected a number"))
+    }
+}
+
+fn flag<'de, D: Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+    match Option::<Lenient>::deserialize(d)? {
+        Some(Lenient::Bool(v)) => Ok(Some(v)),
+        Some(Lenient::Number(v)) => Ok(Some(v != 0.)),
+        Some(Lenient::Text(v)) => match v.trim() {
+            "1" | "true" => Ok(Some(true)),
+            "0" | "false" | "" => Ok(Some(false)),
+            _ => Err(D::Error::custom(format!("expected 0 or 1, found `{}`", v))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn boolean<S: Serializer>(v: &Option<bool>, s: S) -> Result<S::Ok, S::Error> {
+    match v {
+        Some(true) => s.serialize_str("1"),
+        Some(false) => s.serialize_str("0"),
+        None => s.serialize_none(),
+    }
+}
+
+fn enum_value<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: for<'a> TryFrom<&'a [u8], Error = UnexpectedValueError>,
+{
+    match Option::<String>::deserialize(d)? {
+        Some(v) => T::try_from(v.as_bytes())
+            .map(Some)
+            .map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn display<S: Serializer, T: fmt::Display>(v: &Option<T>, s: S) -> Result<S::Ok, S::Error> {
+    match v {
+        Some(v) => s.collect_str(v),
+        None => s.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(steps: &[Step]) -> Vec<StepSpec> {
+        steps.iter().map(StepSpec::from).collect()
+    }
+
+    #[test]
+    fn test_profile() {
+        let payload = include_str!("../fixtures/profile.json");
+        let profile = profile(payload).unwrap();
+        assert_eq!(profile.title.as_deref(), Some("Blooming Espresso"));
+        assert_eq!(profile.author.as_deref(), Some("Decent"));
+        assert_eq!(profile.language.as_deref(), Some("en"));
+        assert_eq!(profile.hidden, Some(false));
+        assert_eq!(profile.beverage_type, Some(BeverageType::Espresso));
+        assert_eq!(profile.target.weight_advanced, Some(60.));
+        assert_eq!(profile.target.volume_advanced_count_start, Some(2.));
+
+        let tcl = include_str!("../../web/profiles/Blooming espresso.tcl");
+        let expected = DecentProfile::parse(tcl.as_bytes()).unwrap();
+        assert_eq!(
+            specs(&profile.shot_steps().unwrap()),
+            specs(&expected.shot_steps().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_profile_numbers() {
+        let payload = r#"{"version": "2", "hidden": true, "steps": [
+            {"name": "fill", "pump": "pressure", "pressure": 3, "seconds": "",
+             "exit": {"type": "flow", "condition": "under", "value": 1.5}}
+        ]}"#;
+        let profile = profile(payload).unwrap();
+        assert_eq!(profile.hidden, Some(true));
+        let spec = StepSpec::from(&profile.steps.unwrap()[0]);
+        assert_eq!(spec.pressure, 3.);
+        assert_eq!(
+            spec.defaulted,
+            vec!["flow", "seconds", "transition", "sensor"]
+        );
+        assert_eq!(
+            spec.exit,
+            Some(crate::model::ExitCondition {
+                kind: ExitType::FlowUnder,
+                value: 1.5
+            })
+        );
+    }
+
+    #[test]
+    fn test_profile_error() {
+        let payload = r#"{"version": "2", "steps": [{"pump": "steam"}]}"#;
+        let e = profile(payload).unwrap_err();
+        assert_eq!(e.line(), 1);
+        assert!(e.to_string().contains("unexpected value `steam`"));
+
+        let payload = r#"{"version": "2", "steps": [{"flow": "fast"}]}"#;
+        let e = profile(payload).unwrap_err();
+        assert!(e.to_string().contains("expected a number, found `fast`"));
+    }
+
+    #[test]
+    fn test_write_profile() {
+        let tcl = include_str!("../../web/profiles/Blooming espresso.tcl");
+        let profile = DecentProfile::parse(tcl.as_bytes()).unwrap();
+        let written: serde_json::Value = serde_json::from_str(&write_profile(&profile)).unwrap();
+        let step = &written["steps"][0];
+        assert_eq!(written["version"], "2");
+        assert_eq!(written["type"], "advanced");
+        assert_eq!(written["legacy_profile_type"], "settings_2c");
+        assert_eq!(written["hidden"], "0");
+        assert_eq!(written["target_weight"], "60");
+        assert_eq!(step["name"], "preinfusion");
+        assert_eq!(step["pump"], "flow");
+        assert_eq!(step["seconds"], "23");
+        assert_eq!(step["exit"]["type"], "pressure");
+        assert_eq!(step["exit"]["condition"], "over");
+        assert_eq!(step["exit"]["value"], "4");
+        assert_eq!(written["steps"][3]["limiter"]["value"], "8.6");
+    }
+}
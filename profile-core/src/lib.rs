@@ -0,0 +1,16 @@
+//! Parser, writer and analysis of Decent Espresso (DE1) profiles.
+//!
+//! Free of any UI dependencies so it builds for both wasm and native targets.
+
+pub mod chart;
+pub mod encoding;
+pub mod json;
+pub mod model;
+pub mod parser;
+pub mod profile;
+mod scale;
+pub mod shot;
+mod tcl;
+pub mod writer;
+
+pub use scale::scale;
@@ -0,0 +1,1054 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::encoding::decode;
+use crate::parser::{
+    profile, steps, BeverageType, Command, ExitType, Number, ParseError, ProfileType, Prop,
+    PumpType, SensorType, Step, TransitionType,
+};
+use crate::writer::write_steps;
+
+/// A profile with every known key decoded into a typed field.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DecentProfile {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub notes: Option<String>,
+    pub language: Option<String>,
+    pub hidden: Option<bool>,
+    pub profile_type: Option<ProfileType>,
+    pub beverage_type: Option<BeverageType>,
+    pub target: Target,
+    pub temperature: Temperature,
+    pub limits: Limits,
+    pub basic: BasicSettings,
+    pub beans: Beans,
+    /// Parsed `advanced_shot`, used as is by "settings_2c" profiles.
+    pub steps: Option<Vec<Step>>,
+    /// Keys this model doesn't know about, in their original order.
+    pub extras: Vec<Command>,
+    /// Commands the profile was parsed from, so unchanged keys are written back in their place
+    /// and with their numbers as written.
+    pub source: Vec<Command>,
+    /// Legacy encoding the file was decoded from, `None` for UTF-8.
+    pub encoding: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Target {
+    pub weight: Option<f32>,
+    pub weight_advanced: Option<f32>,
+    pub volume: Option<f32>,
+    pub volume_advanced: Option<f32>,
+    pub volume_advanced_count_start: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Temperature {
+    pub espresso: Option<f32>,
+    pub steps_enabled: Option<bool>,
+    /// Boost, preinfusion, hold and decline temperatures used when `steps_enabled` is set.
+    pub steps: [Option<f32>; 4],
+    pub tank: Option<f32>,
+    pub water: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    pub maximum_flow: Option<f32>,
+    pub maximum_flow_range: Option<f32>,
+    pub maximum_flow_range_advanced: Option<f32>,
+    pub maximum_flow_range_default: Option<f32>,
+    pub maximum_pressure: Option<f32>,
+    pub maximum_pressure_range: Option<f32>,
+    pub maximum_pressure_range_advanced: Option<f32>,
+    pub maximum_pressure_range_default: Option<f32>,
+}
+
+/// Settings of the "settings_2a" (pressure) and "settings_2b" (flow) profiles.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BasicSettings {
+    pub preinfusion_time: Option<f32>,
+    pub preinfusion_flow_rate: Option<f32>,
+    pub preinfusion_stop_pressure: Option<f32>,
+    pub preinfusion_guarantee: Option<bool>,
+    pub espresso_pressure: Option<f32>,
+    pub espresso_hold_time: Option<f32>,
+    pub espresso_decline_time: Option<f32>,
+    pub pressure_end: Option<f32>,
+    pub flow_profile_preinfusion: Option<f32>,
+    pub flow_profile_preinfusion_time: Option<f32>,
+    pub flow_profile_hold: Option<f32>,
+    pub flow_profile_hold_time: Option<f32>,
+    pub flow_profile_decline: Option<f32>,
+    pub flow_profile_decline_time: Option<f32>,
+    pub flow_profile_minimum_pressure: Option<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Beans {
+    pub brand: Option<String>,
+    pub kind: Option<String>,
+    pub dose_weight: Option<f32>,
+    pub grinder_model: Option<String>,
+    pub grinder_setting: Option<String>,
+}
+
+impl DecentProfile {
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        let decoded = decode(input);
+        let mut profile = Self::try_from(profile(decoded.text.as_bytes())?)?;
+        profile.encoding = decoded.encoding;
+        Ok(profile)
+    }
+
+    /// BCP 47 tag of `language` for `lang` attributes, e.g. the DE1's `zh-hans` is `zh-Hans`.
+    pub fn language_tag(&self) -> Option<String> {
+        let language = self.language.as_deref()?.trim();
+        let tag = match language.to_ascii_lowercase().as_str() {
+            "" => return None,
+            "zh-hans" | "zh_cn" | "zh-cn" => "zh-Hans".into(),
+            "zh-hant" | "zh_tw" | "zh-tw" => "zh-Hant".into(),
+            "kr" => "ko".into(),
+            "jp" => "ja".into(),
+            other => other.replace('_', "-"),
+        };
+        Some(tag)
+    }
+
+    pub fn to_commands(&self) -> Vec<Command> {
+        let mut commands = self.typed_commands();
+        let mut ordered = vec![];
+        for (n, original) in self.source.iter().enumerate() {
+            // NOTE: Like Tcl `array set`, the last value of a repeated key wins
+            if self.source[n + 1..]
+                .iter()
+                .any(|cmd| cmd.key() == original.key())
+            {
+                continue;
+            }
+            let i = commands.iter().position(|cmd| cmd.key() == original.key());
+            if let Some(cmd) = i.map(|i| commands.remove(i)) {
+                if same_value(original, &cmd) {
+                    ordered.push(original.clone());
+                } else {
+                    ordered.push(cmd);
+                }
+            }
+        }
+        // NOTE: Keys set after parsing, or in a profile that wasn't parsed, go in a fixed order
+        ordered.extend(commands);
+        ordered
+    }
+
+    fn typed_commands(&self) -> Vec<Command> {
+        let mut commands = vec![];
+        let mut push = |cmd: Option<Command>| commands.extend(cmd);
+        push(
+            self.steps
+                .as_deref()
+                .map(|steps| Command::AdvancedShot(write_steps(steps))),
+        );
+        push(self.title.clone().map(Command::ProfileTitle));
+        push(self.author.clone().map(Command::Author));
+        push(self.notes.clone().map(Command::ProfileNotes));
+        push(self.language.clone().map(Command::ProfileLanguage));
+        push(self.hidden.map(Command::ProfileHide));
+        push(self.profile_type.map(Command::SettingsProfileType));
+        push(self.beverage_type.map(Command::BeverageType));
+        push(number(self.target.weight, Command::FinalDesiredShotWeight));
+        push(number(
+            self.target.weight_advanced,
+            Command::FinalDesiredShotWeightAdvanced,
+        ));
+        push(number(self.target.volume, Command::FinalDesiredShotVolume));
+        push(number(
+            self.target.volume_advanced,
+            Command::FinalDesiredShotVolumeAdvanced,
+        ));
+        push(number(
+            self.target.volume_advanced_count_start,
+            Command::FinalDesiredShotVolumeAdvancedCountStart,
+        ));
+        push(number(
+            self.temperature.espresso,
+            Command::EspressoTemperature,
+        ));
+        push(
+            self.temperature
+                .steps_enabled
+                .map(Command::EspressoTemperatureStepsEnabled),
+        );
+        push(number(
+            self.temperature.steps[0],
+            Command::EspressoTemperature0,
+        ));
+        push(number(
+            self.temperature.steps[1],
+            Command::EspressoTemperature1,
+        ));
+        push(number(
+            self.temperature.steps[2],
+            Command::EspressoTemperature2,
+        ));
+        push(number(
+            self.temperature.steps[3],
+            Command::EspressoTemperature3,
+        ));
+        push(number(
+            self.temperature.tank,
+            Command::TankDesiredWaterTemperature,
+        ));
+        push(number(self.temperature.water, Command::WaterTemperature));
+        push(number(self.limits.maximum_flow, Command::MaximumFlow));
+        push(number(
+            self.limits.maximum_flow_range,
+            Command::MaximumFlowRange,
+        ));
+        push(number(
+            self.limits.maximum_flow_range_advanced,
+            Command::MaximumFlowRangeAdvanced,
+        ));
+        push(number(
+            self.limits.maximum_flow_range_default,
+            Command::MaximumFlowRangeDefault,
+        ));
+        push(number(
+            self.limits.maximum_pressure,
+            Command::MaximumPressure,
+        ));
+        push(number(
+            self.limits.maximum_pressure_range,
+            Command::MaximumPressureRange,
+        ));
+        push(number(
+            self.limits.maximum_pressure_range_advanced,
+            Command::MaximumPressureRangeAdvanced,
+        ));
+        push(number(
+            self.limits.maximum_pressure_range_default,
+            Command::MaximumPressureRangeDefault,
+        ));
+        push(number(
+            self.basic.preinfusion_time,
+            Command::PreinfusionTime,
+        ));
+        push(number(
+            self.basic.preinfusion_flow_rate,
+            Command::PreinfusionFlowRate,
+        ));
+        push(number(
+            self.basic.preinfusion_stop_pressure,
+            Command::PreinfusionStopPressure,
+        ));
+        push(
+            self.basic
+                .preinfusion_guarantee
+                .map(Command::PreinfusionGuarantee),
+        );
+        push(number(
+            self.basic.espresso_pressure,
+            Command::EspressoPressure,
+        ));
+        push(number(
+            self.basic.espresso_hold_time,
+            Command::EspressoHoldTime,
+        ));
+        push(number(
+            self.basic.espresso_decline_time,
+            Command::EspressoDeclineTime,
+        ));
+        push(number(self.basic.pressure_end, Command::PressureEnd));
+        push(number(
+            self.basic.flow_profile_preinfusion,
+            Command::FlowProfilePreinfusion,
+        ));
+        push(number(
+            self.basic.flow_profile_preinfusion_time,
+            Command::FlowProfilePreinfusionTime,
+        ));
+        push(number(
+            self.basic.flow_profile_hold,
+            Command::FlowProfileHold,
+        ));
+        push(number(
+            self.basic.flow_profile_hold_time,
+            Command::FlowProfileHoldTime,
+        ));
+        push(number(
+            self.basic.flow_profile_decline,
+            Command::FlowProfileDecline,
+        ));
+        push(number(
+            self.basic.flow_profile_decline_time,
+            Command::FlowProfileDeclineTime,
+        ));
+        push(number(
+            self.basic.flow_profile_minimum_pressure,
+            Command::FlowProfileMinimumPressure,
+        ));
+        push(self.beans.brand.clone().map(Command::BeanBrand));
+        push(self.beans.kind.clone().map(Command::BeanType));
+        push(number(self.beans.dose_weight, Command::GrinderDoseWeight));
+        push(self.beans.grinder_model.clone().map(Command::GrinderModel));
+        push(
+            self.beans
+                .grinder_setting
+                .clone()
+                .map(Command::GrinderSetting),
+        );
+        commands.extend(self.extras.iter().cloned());
+        commands
+    }
+
+    pub fn is_profile_type(&self, ty: ProfileType) -> bool {
+        self.profile_type == Some(ty)
+    }
+
+    /// Things that were accepted while parsing but likely need the author's attention.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = vec![];
+        if let Some(encoding) = self.encoding {
+            warnings.push(Warning {
+                key: "encoding".into(),
+                message: format!("the file is not UTF-8 and was decoded as {}", encoding),
+            });
+        }
+        for cmd in self.extras.iter() {
+            warnings.push(Warning {
+                key: cmd.key().to_string(),
+                message: format!("`{}` is not recognized and was kept as is", cmd.key()),
+            });
+        }
+
+        let steps = match self.profile_type {
+            Some(ProfileType::Settings2C) => self.steps.as_deref().unwrap_or_default(),
+            _ => &[],
+        };
+        for (i, step) in steps.iter().enumerate() {
+            let spec = StepSpec::from(step);
+            for prop in step.0.iter() {
+                if let Prop::Unknown((key, _)) = prop {
+                    warnings.push(Warning {
+                        key: key.clone(),
+                        message: format!(
+                            "`{}` in step {} is not recognized and was kept as is",
+                            key,
+                            i + 1
+                        ),
+                    });
+                }
+            }
+            for key in spec.defaulted {
+                warnings.push(Warning {
+                    key: key.to_string(),
+                    message: format!(
+                        "`{}` is missing in step {}, the DE1 default is used",
+                        key,
+                        i + 1
+                    ),
+                });
+            }
+        }
+        warnings
+    }
+
+    /// Steps the machine runs for this profile, or `None` for unsupported profile types.
+    pub fn shot_steps(&self) -> Option<Vec<Step>> {
+        match self.profile_type? {
+            ProfileType::Settings2A => Some(self.pressure_steps()),
+            ProfileType::Settings2B => Some(self.flow_steps()),
+            ProfileType::Settings2C => Some(self.steps.clone().unwrap_or_default()),
+            _ => None,
+        }
+    }
+
+    /// Expands a "settings_2a" profile into steps the same way the DE1 app does.
+    pub fn pressure_steps(&self) -> Vec<Step> {
+        let basic = &self.basic;
+        let preinfusion = Step(vec![
+            Prop::Name("preinfusion".into()),
+            Prop::Pump(PumpType::Flow),
+            Prop::Transition(TransitionType::Fast),
+            Prop::Flow(basic.preinfusion_flow_rate.unwrap_or_default().into()),
+            Prop::Pressure(1.0.into()),
+            Prop::ExitIf(true),
+            Prop::ExitType(ExitType::PressureOver),
+            Prop::ExitPressureOver(basic.preinfusion_stop_pressure.unwrap_or_default().into()),
+        ]);
+        let mut hold = Step(vec![
+            Prop::Name("rise and hold".into()),
+            Prop::Pump(PumpType::Pressure),
+            Prop::Transition(TransitionType::Fast),
+            Prop::Pressure(basic.espresso_pressure.unwrap_or_default().into()),
+            Prop::ExitIf(false),
+        ]);
+        hold.0.extend(self.flow_limiter());
+        let mut decline = Step(vec![
+            Prop::Name("decline".into()),
+            Prop::Pump(PumpType::Pressure),
+            Prop::Transition(TransitionType::Smooth),
+            Prop::Pressure(basic.pressure_end.unwrap_or_default().into()),
+            Prop::ExitIf(false),
+        ]);
+        decline.0.extend(self.flow_limiter());
+
+        self.expand(
+            [preinfusion, hold, decline],
+            [
+                basic.preinfusion_time.unwrap_or_default(),
+                basic.espresso_hold_time.unwrap_or_default(),
+                basic.espresso_decline_time.unwrap_or_default(),
+            ],
+        )
+    }
+
+    /// Expands a "settings_2b" profile into steps the same way the DE1 app does.
+    pub fn flow_steps(&self) -> Vec<Step> {
+        let basic = &self.basic;
+        let preinfusion = Step(vec![
+            Prop::Name("preinfusion".into()),
+            Prop::Pump(PumpType::Flow),
+            Prop::Transition(TransitionType::Fast),
+            Prop::Flow(basic.flow_profile_preinfusion.unwrap_or_default().into()),
+            Prop::Pressure(1.0.into()),
+            Prop::ExitIf(true),
+            Prop::ExitType(ExitType::PressureOver),
+            Prop::ExitPressureOver(basic.preinfusion_stop_pressure.unwrap_or_default().into()),
+        ]);
+        let mut hold = Step(vec![
+            Prop::Name("hold".into()),
+            Prop::Pump(PumpType::Flow),
+            Prop::Transition(TransitionType::Fast),
+            Prop::Flow(basic.flow_profile_hold.unwrap_or_default().into()),
+            Prop::ExitIf(false),
+        ]);
+        hold.0.extend(self.pressure_limiter());
+        let mut decline = Step(vec![
+            Prop::Name("decline".into()),
+            Prop::Pump(PumpType::Flow),
+            Prop::Transition(TransitionType::Smooth),
+            Prop::Flow(basic.flow_profile_decline.unwrap_or_default().into()),
+            Prop::ExitIf(false),
+        ]);
+        decline.0.extend(self.pressure_limiter());
+
+        self.expand(
+            [preinfusion, hold, decline],
+            [
+                basic.flow_profile_preinfusion_time.unwrap_or_default(),
+                basic.flow_profile_hold_time.unwrap_or_default(),
+                basic.flow_profile_decline_time.unwrap_or_default(),
+            ],
+        )
+    }
+
+    /// Flow limit the DE1 app puts on the pressure steps of a "settings_2a" profile.
+    fn flow_limiter(&self) -> Vec<Prop> {
+        limiter(
+            self.limits.maximum_flow,
+            self.limits.maximum_flow_range_default,
+        )
+    }
+
+    /// Pressure limit the DE1 app puts on the flow steps of a "settings_2b" profile.
+    fn pressure_limiter(&self) -> Vec<Prop> {
+        limiter(
+            self.limits.maximum_pressure,
+            self.limits.maximum_pressure_range_default,
+        )
+    }
+
+    /// Adds temperature, sensor and duration to the preinfusion, hold and decline steps,
+    /// splitting a temperature boost off the preinfusion and dropping steps of no length.
+    fn expand(&self, steps: [Step; 3], seconds: [f32; 3]) -> Vec<Step> {
+        let [preinfusion, hold, decline] = steps;
+        let [preinfusion_time, hold_time, decline_time] = seconds;
+
+        let mut timed = vec![];
+        if self.temperature.steps_enabled == Some(true) {
+            let [boost_t, preinfusion_t, hold_t, decline_t] =
+                self.temperature.steps.map(Option::unwrap_or_default);
+            let boost_time = preinfusion_time.min(TEMPERATURE_BOOST_SECONDS);
+            let mut boost = preinfusion.clone();
+            boost.0[0] = Prop::Name("preinfusion temp boost".into());
+            timed.push((boost, boost_t, boost_time));
+            timed.push((preinfusion, preinfusion_t, preinfusion_time - boost_time));
+            timed.push((hold, hold_t, hold_time));
+            timed.push((decline, decline_t, decline_time));
+        } else {
+            let t = self.temperature.espresso.unwrap_or_default();
+            timed.push((preinfusion, t, preinfusion_time));
+            timed.push((hold, t, hold_time));
+            timed.push((decline, t, decline_time));
+        }
+
+        timed
+            .into_iter()
+            .filter(|(_, _, seconds)| *seconds > 0.)
+            .map(|(mut step, temperature, seconds)| {
+                step.0.push(Prop::Temperature(temperature.into()));
+                step.0.push(Prop::Sensor(SensorType::Coffee));
+                step.0.push(Prop::Seconds(seconds.into()));
+                step
+            })
+            .collect()
+    }
+}
+
+fn limiter(value: Option<f32>, range: Option<f32>) -> Vec<Prop> {
+    // NOTE: Zero turns the limiter off, so there is nothing to put on the step
+    match value.filter(|v| *v > 0.) {
+        Some(value) => {
+            let mut props = vec![Prop::MaxFlowOrPressure(value.into())];
+            props.extend(range.map(|range| Prop::MaxFlowOrPressureRange(range.into())));
+            props
+        }
+        None => vec![],
+    }
+}
+
+/// Length of the hotter first part of preinfusion when temperature steps are enabled.
+const TEMPERATURE_BOOST_SECONDS: f32 = 2.;
+
+impl TryFrom<Vec<Command>> for DecentProfile {
+    type Error = ParseError;
+
+    fn try_from(commands: Vec<Command>) -> Result<Self, Self::Error> {
+        let mut profile = DecentProfile {
+            source: commands.clone(),
+            ..Default::default()
+        };
+        for cmd in commands {
+            match cmd {
+                Command::AdvancedShot(data) => profile.steps = Some(steps(data.as_bytes())?),
+                Command::ProfileTitle(v) => profile.title = Some(v),
+                Command::Author(v) => profile.author = Some(v),
+                Command::ProfileNotes(v) => profile.notes = Some(v),
+                Command::ProfileLanguage(v) => profile.language = Some(v),
+                Command::ProfileHide(v) => profile.hidden = Some(v),
+                Command::SettingsProfileType(v) => profile.profile_type = Some(v),
+                Command::BeverageType(v) => profile.beverage_type = Some(v),
+                Command::FinalDesiredShotWeight(v) => profile.target.weight = Some(v.value),
+                Command::FinalDesiredShotWeightAdvanced(v) => {
+                    profile.target.weight_advanced = Some(v.value)
+                }
+                Command::FinalDesiredShotVolume(v) => profile.target.volume = Some(v.value),
+                Command::FinalDesiredShotVolumeAdvanced(v) => {
+                    profile.target.volume_advanced = Some(v.value)
+                }
+                Command::FinalDesiredShotVolumeAdvancedCountStart(v) => {
+                    profile.target.volume_advanced_count_start = Some(v.value)
+                }
+                Command::EspressoTemperature(v) => profile.temperature.espresso = Some(v.value),
+                Command::EspressoTemperatureStepsEnabled(v) => {
+                    profile.temperature.steps_enabled = Some(v)
+                }
+                Command::EspressoTemperature0(v) => profile.temperature.steps[0] = Some(v.value),
+                Command::EspressoTemperature1(v) => profile.temperature.steps[1] = Some(v.value),
+                Command::EspressoTemperature2(v) => profile.temperature.steps[2] = Some(v.value),
+                Command::EspressoTemperature3(v) => profile.temperature.steps[3] = Some(v.value),
+                Command::TankDesiredWaterTemperature(v) => profile.temperature.tank = Some(v.value),
+                Command::WaterTemperature(v) => profile.temperature.water = Some(v.value),
+                Command::MaximumFlow(v) => profile.limits.maximum_flow = Some(v.value),
+                Command::MaximumFlowRange(v) => profile.limits.maximum_flow_range = Some(v.value),
+                Command::MaximumFlowRangeAdvanced(v) => {
+                    profile.limits.maximum_flow_range_advanced = Some(v.value)
+                }
+                Command::MaximumFlowRangeDefault(v) => {
+                    profile.limits.maximum_flow_range_default = Some(v.value)
+                }
+                Command::MaximumPressure(v) => profile.limits.maximum_pressure = Some(v.value),
+                Command::MaximumPressureRange(v) => {
+                    profile.limits.maximum_pressure_range = Some(v.value)
+                }
+                Command::MaximumPressureRangeAdvanced(v) => {
+                    profile.limits.maximum_pressure_range_advanced = Some(v.value)
+                }
+                Command::MaximumPressureRangeDefault(v) => {
+                    profile.limits.maximum_pressure_range_default = Some(v.value)
+                }
+                Command::PreinfusionTime(v) => profile.basic.preinfusion_time = Some(v.value),
+                Command::PreinfusionFlowRate(v) => {
+                    profile.basic.preinfusion_flow_rate = Some(v.value)
+                }
+                Command::PreinfusionStopPressure(v) => {
+                    profile.basic.preinfusion_stop_pressure = Some(v.value)
+                }
+                Command::PreinfusionGuarantee(v) => profile.basic.preinfusion_guarantee = Some(v),
+                Command::EspressoPressure(v) => profile.basic.espresso_pressure = Some(v.value),
+                Command::EspressoHoldTime(v) => profile.basic.espresso_hold_time = Some(v.value),
+                Command::EspressoDeclineTime(v) => {
+                    profile.basic.espresso_decline_time = Some(v.value)
+                }
+                Command::PressureEnd(v) => profile.basic.pressure_end = Some(v.value),
+                Command::FlowProfilePreinfusion(v) => {
+                    profile.basic.flow_profile_preinfusion = Some(v.value)
+                }
+                Command::FlowProfilePreinfusionTime(v) => {
+                    profile.basic.flow_profile_preinfusion_time = Some(v.value)
+                }
+                Command::FlowProfileHold(v) => profile.basic.flow_profile_hold = Some(v.value),
+                Command::FlowProfileHoldTime(v) => {
+                    profile.basic.flow_profile_hold_time = Some(v.value)
+                }
+                Command::FlowProfileDecline(v) => {
+                    profile.basic.flow_profile_decline = Some(v.value)
+                }
+                Command::FlowProfileDeclineTime(v) => {
+                    profile.basic.flow_profile_decline_time = Some(v.value)
+                }
+                Command::FlowProfileMinimumPressure(v) => {
+                    profile.basic.flow_profile_minimum_pressure = Some(v.value)
+                }
+                Command::BeanBrand(v) => profile.beans.brand = Some(v),
+                Command::BeanType(v) => profile.beans.kind = Some(v),
+                Command::GrinderDoseWeight(v) => profile.beans.dose_weight = Some(v.value),
+                Command::GrinderModel(v) => profile.beans.grinder_model = Some(v),
+                Command::GrinderSetting(v) => profile.beans.grinder_setting = Some(v),
+                Command::Unknown(_) => {
+                    profile.extras.retain(|extra| extra.key() != cmd.key());
+                    profile.extras.push(cmd)
+                }
+            }
+        }
+        Ok(profile)
+    }
+}
+
+impl From<&DecentProfile> for Vec<Command> {
+    fn from(profile: &DecentProfile) -> Self {
+        profile.to_commands()
+    }
+}
+
+fn number(v: Option<f32>, cmd: fn(Number) -> Command) -> Option<Command> {
+    v.map(|v| cmd(v.into()))
+}
+
+/// Whether two commands of the same key hold the same value, whatever way it is written.
+fn same_value(a: &Command, b: &Command) -> bool {
+    match (a, b) {
+        (Command::AdvancedShot(a), Command::AdvancedShot(b)) => {
+            steps(a.as_bytes()).ok() == steps(b.as_bytes()).ok()
+        }
+        _ => a == b,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A step with DE1 firmware defaults filled in for the keys it omits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepSpec {
+    pub name: String,
+    pub pump: PumpType,
+    pub transition: TransitionType,
+    pub sensor: SensorType,
+    pub temperature: Option<f32>,
+    pub pressure: f32,
+    pub flow: f32,
+    pub seconds: f32,
+    /// Volume limit in ml, `None` when the step doesn't stop on volume.
+    pub volume: Option<f32>,
+    /// Weight limit in g, `None` when the step doesn't stop on weight.
+    pub weight: Option<f32>,
+    pub exit: Option<ExitCondition>,
+    pub limiter: Option<Limiter>,
+    /// Keys missing from the step whose value came from the defaults.
+    pub defaulted: Vec<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExitCondition {
+    pub kind: ExitType,
+    pub value: f32,
+}
+
+/// Flow limit on a pressure step, or pressure limit on a flow step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limiter {
+    pub value: f32,
+    pub range: f32,
+}
+
+/// Range the firmware applies when a step sets a limiter without one.
+const LIMITER_RANGE: f32 = 0.6;
+
+impl From<&Step> for StepSpec {
+    fn from(step: &Step) -> Self {
+        let mut defaulted = vec![];
+        let mut or_default = |key: &'static str, value: Option<f32>| {
+            value.unwrap_or_else(|| {
+                defaulted.push(key);
+                0.
+            })
+        };
+        let pressure = or_default("pressure", step.number("pressure"));
+        let flow = or_default("flow", step.number("flow"));
+        // NOTE: Negative lengths are read as written, but time can't run backwards
+        let seconds = or_default("seconds", step.seconds()).max(0.);
+
+        let pump = step.pump().unwrap_or_else(|| {
+            defaulted.push("pump");
+            PumpType::Pressure
+        });
+        let transition = step.transition().unwrap_or_else(|| {
+            defaulted.push("transition");
+            TransitionType::Fast
+        });
+        let sensor = step.sensor().unwrap_or_else(|| {
+            defaulted.push("sensor");
+            SensorType::Coffee
+        });
+
+        let exit = match (step.exit_if(), step.exit_type()) {
+            (Some(true), Some(kind)) => {
+                let key = match kind {
+                    ExitType::PressureUnder => "exit_pressure_under",
+                    ExitType::PressureOver => "exit_pressure_over",
+                    ExitType::FlowUnder => "exit_flow_under",
+                    ExitType::FlowOver => "exit_flow_over",
+                };
+                step.number(key).map(|value| ExitCondition { kind, value })
+            }
+            _ => None,
+        };
+        let limiter = step
+            .number("max_flow_or_pressure")
+            .filter(|v| *v > 0.)
+            .map(|value| Limiter {
+                value,
+                range: step
+                    .number("max_flow_or_pressure_range")
+                    .unwrap_or(LIMITER_RANGE),
+            });
+
+        StepSpec {
+            name: step.name().unwrap_or_default().to_string(),
+            pump,
+            transition,
+            sensor,
+            temperature: step.number("temperature"),
+            pressure,
+            flow,
+            seconds,
+            volume: step.number("volume").filter(|v| *v > 0.),
+            weight: step.number("weight").filter(|v| *v > 0.),
+            exit,
+            limiter,
+            defaulted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_is_profile_type() {
+        let profile =
+            DecentProfile::try_from(vec![Command::SettingsProfileType(ProfileType::Settings2C)])
+                .unwrap();
+        assert!(profile.is_profile_type(ProfileType::Settings2C));
+    }
+
+    #[test]
+    fn test_profile_is_profile_type_failed() {
+        let profile =
+            DecentProfile::try_from(vec![Command::SettingsProfileType(ProfileType::Settings1)])
+                .unwrap();
+        assert!(!profile.is_profile_type(ProfileType::Settings2C));
+
+        let profile = DecentProfile::try_from(vec![Command::Author("Trunk".into())]).unwrap();
+        assert!(!profile.is_profile_type(ProfileType::Settings2C));
+    }
+
+    #[test]
+    fn test_parse_profile_file() {
+        let payload = include_str!("../fixtures/profile.tcl");
+        let profile = DecentProfile::parse(payload.as_bytes()).unwrap();
+        assert_eq!(profile.title.as_deref(), Some("Filter 2.1"));
+        assert_eq!(profile.author.as_deref(), Some("Decent"));
+        assert_eq!(profile.beverage_type, Some(BeverageType::Pourover));
+        assert_eq!(profile.target.weight_advanced, Some(100.));
+        assert_eq!(
+            profile.temperature.steps,
+            [Some(90.), Some(88.), Some(88.), Some(88.)]
+        );
+        assert_eq!(profile.limits.maximum_pressure_range_default, Some(0.9));
+        assert_eq!(profile.basic.flow_profile_decline_time, Some(17.));
+        assert_eq!(
+            profile.steps,
+            Some(vec![
+                Step(vec![Prop::ExitIf(false), Prop::Flow(4.0.into())]),
+                Step(vec![
+                    Prop::Temperature(98.0.into()),
+                    Prop::Name("3 mL/s".into()),
+                    Prop::Seconds(60.0.into()),
+                ]),
+            ])
+        );
+        assert!(profile.extras.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_advanced_shot() {
+        let result = DecentProfile::try_from(vec![Command::AdvancedShot("{pump air}".into())]);
+        assert_eq!(result.unwrap_err().key.as_deref(), Some("pump"));
+    }
+
+    #[test]
+    fn test_to_commands_keeps_source() {
+        let mut profile = DecentProfile::parse(
+            b"profile_title Ristretto\nespresso_pressure 8.40\nmy_counter 1\nauthor Decent\n\
+              advanced_shot {{seconds 20.0 pressure 9}}\ntank_desired_water_temperature 0\n",
+        )
+        .unwrap();
+        profile.basic.espresso_pressure = Some(9.);
+        profile.temperature.tank = None;
+        profile.beans.brand = Some("Decent".into());
+        assert_eq!(
+            crate::writer::write_profile(&profile.to_commands()),
+            "profile_title Ristretto\nespresso_pressure 9\nmy_counter 1\nauthor Decent\n\
+             advanced_shot {{seconds 20.0 pressure 9}}\nbean_brand Decent\n"
+        );
+    }
+
+    #[test]
+    fn test_to_commands_repeated_key() {
+        let profile = DecentProfile::parse(
+            b"espresso_pressure 8.40\nmy_counter 1\nprofile_title Ristretto\n\
+              espresso_pressure 9.0\nmy_counter 2\n",
+        )
+        .unwrap();
+        assert_eq!(profile.basic.espresso_pressure, Some(9.));
+        assert_eq!(
+            crate::writer::write_profile(&profile.to_commands()),
+            "profile_title Ristretto\nespresso_pressure 9.0\nmy_counter 2\n"
+        );
+    }
+
+    #[test]
+    fn test_pressure_steps() {
+        let profile = DecentProfile::try_from(vec![
+            Command::SettingsProfileType(ProfileType::Settings2A),
+            Command::PreinfusionTime(8.0.into()),
+            Command::PreinfusionFlowRate(4.5.into()),
+            Command::PreinfusionStopPressure(4.0.into()),
+            Command::EspressoHoldTime(35.0.into()),
+            Command::EspressoPressure(9.0.into()),
+            Command::EspressoDeclineTime(0.0.into()),
+            Command::PressureEnd(6.0.into()),
+            Command::EspressoTemperature(94.0.into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            profile.shot_steps(),
+            Some(vec![
+                Step(vec![
+                    Prop::Name("preinfusion".into()),
+                    Prop::Pump(PumpType::Flow),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::Flow(4.5.into()),
+                    Prop::Pressure(1.0.into()),
+                    Prop::ExitIf(true),
+                    Prop::ExitType(ExitType::PressureOver),
+                    Prop::ExitPressureOver(4.0.into()),
+                    Prop::Temperature(94.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Seconds(8.0.into()),
+                ]),
+                Step(vec![
+                    Prop::Name("rise and hold".into()),
+                    Prop::Pump(PumpType::Pressure),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::Pressure(9.0.into()),
+                    Prop::ExitIf(false),
+                    Prop::Temperature(94.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Seconds(35.0.into()),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_flow_steps_with_temperature_steps() {
+        let profile = DecentProfile::try_from(vec![
+            Command::SettingsProfileType(ProfileType::Settings2B),
+            Command::FlowProfilePreinfusion(4.0.into()),
+            Command::FlowProfilePreinfusionTime(5.0.into()),
+            Command::FlowProfileHold(2.0.into()),
+            Command::FlowProfileHoldTime(8.0.into()),
+            Command::FlowProfileDecline(1.2.into()),
+            Command::FlowProfileDeclineTime(17.0.into()),
+            Command::EspressoTemperatureStepsEnabled(true),
+            Command::EspressoTemperature0(92.0.into()),
+            Command::EspressoTemperature1(90.0.into()),
+            Command::EspressoTemperature2(88.0.into()),
+            Command::EspressoTemperature3(86.0.into()),
+        ])
+        .unwrap();
+        let summary = profile
+            .shot_steps()
+            .unwrap()
+            .iter()
+            .map(|step| {
+                let spec = StepSpec::from(step);
+                (spec.seconds, spec.pump, spec.transition, spec.temperature)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (2., PumpType::Flow, TransitionType::Fast, Some(92.)),
+                (3., PumpType::Flow, TransitionType::Fast, Some(90.)),
+                (8., PumpType::Flow, TransitionType::Fast, Some(88.)),
+                (17., PumpType::Flow, TransitionType::Smooth, Some(86.)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flow_steps_limiter() {
+        let profile = DecentProfile::parse(include_bytes!(
+            "../../web/profiles/Flow profile for straight espresso.tcl"
+        ))
+        .unwrap();
+        let limiters = profile
+            .shot_steps()
+            .unwrap()
+            .iter()
+            .map(|step| StepSpec::from(step).limiter)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            limiters,
+            vec![
+                None,
+                Some(Limiter {
+                    value: 8.6,
+                    range: 0.9
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_spec_defaults() {
+        let step = &steps(b"{name pour pressure 9 seconds 20}").unwrap()[0];
+        let spec = StepSpec::from(step);
+        assert_eq!(spec.name, "pour");
+        assert_eq!(spec.pump, PumpType::Pressure);
+        assert_eq!(spec.transition, TransitionType::Fast);
+        assert_eq!(spec.sensor, SensorType::Coffee);
+        assert_eq!(spec.temperature, None);
+        assert_eq!(spec.flow, 0.);
+        assert_eq!(spec.exit, None);
+        assert_eq!(spec.limiter, None);
+        assert_eq!(spec.defaulted, vec!["flow", "pump", "transition", "sensor"]);
+    }
+
+    #[test]
+    fn test_step_spec_negative_amounts() {
+        let step = &steps(b"{name pour pressure 9 seconds -5 volume -1 weight -2.5}").unwrap()[0];
+        assert_eq!(step.seconds(), Some(-5.));
+        let spec = StepSpec::from(step);
+        assert_eq!(spec.seconds, 0.);
+        assert_eq!(spec.volume, None);
+        assert_eq!(spec.weight, None);
+    }
+
+    #[test]
+    fn test_step_spec_exit_and_limiter() {
+        let step = &steps(
+            b"{exit_if 1 flow 2 volume 100 max_flow_or_pressure_range 0.2 transition smooth \
+              exit_flow_under 0 temperature 93 weight 0 name fill pressure 4 sensor water \
+              pump flow exit_type pressure_over exit_pressure_over 3.5 \
+              max_flow_or_pressure 6 exit_pressure_under 0 seconds 25}",
+        )
+        .unwrap()[0];
+        let spec = StepSpec::from(step);
+        assert_eq!(
+            spec.exit,
+            Some(ExitCondition {
+                kind: ExitType::PressureOver,
+                value: 3.5
+            })
+        );
+        assert_eq!(
+            spec.limiter,
+            Some(Limiter {
+                value: 6.,
+                range: 0.2
+            })
+        );
+        assert_eq!(spec.volume, Some(100.));
+        assert_eq!(spec.weight, None);
+        assert_eq!(spec.sensor, SensorType::Water);
+        assert!(spec.defaulted.is_empty());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let profile = DecentProfile::parse(
+            b"advanced_shot {{name fill pressure 4 flow 6 seconds 10 pump pressure sensor coffee \
+              color red}}\nsettings_profile_type settings_2c\nfancy_option 1\n",
+        )
+        .unwrap();
+        let warnings = profile
+            .warnings()
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "`fancy_option` is not recognized and was kept as is",
+                "`color` in step 1 is not recognized and was kept as is",
+                "`transition` is missing in step 1, the DE1 default is used",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy_encoding() {
+        let profile = DecentProfile::parse(
+            b"profile_title {Caf\xe9 cr\xe8me}\nprofile_language de\nsettings_profile_type settings_2c\n",
+        )
+        .unwrap();
+        assert_eq!(profile.title.as_deref(), Some("Café crème"));
+        assert_eq!(profile.encoding, Some("windows-1252"));
+        assert_eq!(
+            profile.warnings()[0].to_string(),
+            "the file is not UTF-8 and was decoded as windows-1252"
+        );
+        assert_eq!(profile.language_tag().as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn test_language_tag() {
+        let tag = |language: &str| {
+            DecentProfile {
+                language: Some(language.into()),
+                ..Default::default()
+            }
+            .language_tag()
+        };
+        assert_eq!(tag("zh-hans").as_deref(), Some("zh-Hans"));
+        assert_eq!(tag("kr").as_deref(), Some("ko"));
+        assert_eq!(tag("pt_BR").as_deref(), Some("pt-br"));
+        assert_eq!(tag(""), None);
+    }
+}
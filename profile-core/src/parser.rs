@@ -0,0 +1,1667 @@
+use std::{convert::TryFrom, fmt, str};
+
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case, take_till, take_while1};
+use nom::character::{
+    complete::{digit0, digit1, multispace0, multispace1, one_of, space1},
+    is_newline, is_space,
+};
+use nom::combinator::{map, map_res, opt, recognize, verify};
+use nom::multi::{many0, separated_list0};
+use nom::sequence::{delimited, preceded, tuple};
+use nom::IResult;
+
+use crate::tcl::word;
+
+/// A number with the text it was read from, so that writing it back doesn't round it to `f32`.
+#[derive(Clone, Debug)]
+pub struct Number {
+    pub value: f32,
+    lexeme: Option<String>,
+}
+
+impl Number {
+    /// Text as written in the file, or the shortest form of the value when it wasn't parsed.
+    pub fn lexeme(&self) -> String {
+        self.lexeme
+            .clone()
+            .unwrap_or_else(|| format!("{}", self.value))
+    }
+}
+
+impl From<f32> for Number {
+    fn from(value: f32) -> Self {
+        Self {
+            value,
+            lexeme: None,
+        }
+    }
+}
+
+// NOTE: The lexeme is only presentation, so `8` and `8.0` are the same number
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.lexeme {
+            Some(lexeme) => f.write_str(lexeme),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prop {
+    ExitIf(bool),
+    Flow(Number),
+    Volume(Number),
+    MaxFlowOrPressureRange(Number),
+    Transition(TransitionType),
+    ExitFlowUnder(Number),
+    Temperature(Number),
+    Name(String),
+    Pressure(Number),
+    Sensor(SensorType),
+    Pump(PumpType),
+    ExitType(ExitType),
+    ExitFlowOver(Number),
+    ExitPressureOver(Number),
+    MaxFlowOrPressure(Number),
+    ExitPressureUnder(Number),
+    Seconds(Number),
+    Weight(Number),
+    Unknown((String, String)),
+}
+
+impl Prop {
+    pub fn key(&self) -> &str {
+        match self {
+            Prop::ExitIf(_) => "exit_if",
+            Prop::Flow(_) => "flow",
+            Prop::Volume(_) => "volume",
+            Prop::MaxFlowOrPressureRange(_) => "max_flow_or_pressure_range",
+            Prop::Transition(_) => "transition",
+            Prop::ExitFlowUnder(_) => "exit_flow_under",
+            Prop::Temperature(_) => "temperature",
+            Prop::Name(_) => "name",
+            Prop::Pressure(_) => "pressure",
+            Prop::Sensor(_) => "sensor",
+            Prop::Pump(_) => "pump",
+            Prop::ExitType(_) => "exit_type",
+            Prop::ExitFlowOver(_) => "exit_flow_over",
+            Prop::ExitPressureOver(_) => "exit_pressure_over",
+            Prop::MaxFlowOrPressure(_) => "max_flow_or_pressure",
+            Prop::ExitPressureUnder(_) => "exit_pressure_under",
+            Prop::Seconds(_) => "seconds",
+            Prop::Weight(_) => "weight",
+            Prop::Unknown((name, _)) => name,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            Prop::Flow(v)
+            | Prop::Volume(v)
+            | Prop::MaxFlowOrPressureRange(v)
+            | Prop::ExitFlowUnder(v)
+            | Prop::Temperature(v)
+            | Prop::Pressure(v)
+            | Prop::ExitFlowOver(v)
+            | Prop::ExitPressureOver(v)
+            | Prop::MaxFlowOrPressure(v)
+            | Prop::ExitPressureUnder(v)
+            | Prop::Seconds(v)
+            | Prop::Weight(v) => Some(v.value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConvertError(String);
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot convert {}", self.0)
+    }
+}
+
+impl TryFrom<TransitionType> for Prop {
+    type Error = ConvertError;
+
+    fn try_from(value: TransitionType) -> Result<Self, Self::Error> {
+        Ok(Prop::Transition(value))
+    }
+}
+
+trait ParsableEnumProp {
+    fn parse(i: &[u8]) -> IResult<&[u8], Prop>;
+}
+
+#[derive(Clone, Debug)]
+pub struct UnexpectedValueError(String);
+
+impl fmt::Display for UnexpectedValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unexpected value `{}`", self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub key: Option<String>,
+    pub expected: String,
+    pub unconsumed: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(input: &[u8], rest: &[u8], key: Option<String>, expected: &str) -> Self {
+        Self::at(input, input.len() - rest.len(), key, expected.into())
+    }
+
+    fn at(input: &[u8], offset: usize, key: Option<String>, expected: String) -> Self {
+        let consumed = &input[..offset];
+        let line_start = consumed
+            .iter()
+            .rposition(|c| is_newline(*c))
+            .map_or(0, |pos| pos + 1);
+        Self {
+            offset,
+            line: consumed.iter().filter(|c| is_newline(**c)).count() + 1,
+            column: String::from_utf8_lossy(&consumed[line_start..])
+                .chars()
+                .count()
+                + 1,
+            key,
+            expected,
+            unconsumed: input.len() - offset,
+        }
+    }
+
+    /// Re-anchors an error found in the value of `key` onto the whole `input`, at `base` when the
+    /// value is in `input` byte by byte from there, or else at `i`.
+    pub(crate) fn relocate(self, input: &[u8], base: Option<usize>, i: &[u8], key: &str) -> Self {
+        match base {
+            Some(base) => Self::at(input, base + self.offset, self.key, self.expected),
+            // NOTE: Quoted or escaped values don't map back byte by byte
+            None => Self::new(input, i, Some(key.into()), &self.expected),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.key {
+            Some(key) => write!(f, "expected {} for `{}`", self.expected, key),
+            None => write!(f, "expected {}", self.expected),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn key_at(i: &[u8]) -> Option<String> {
+    let (_, key) = take_till::<_, _, nom::error::Error<_>>(|c| is_space(c) || is_newline(c))(i)
+        .expect("take_till never fails on complete input");
+    if key.is_empty() {
+        None
+    } else {
+        Some(String::from_utf8_lossy(key).into_owned())
+    }
+}
+
+pub(crate) fn skip_space(i: &[u8]) -> &[u8] {
+    let (i, _) = multispace0::<_, nom::error::Error<_>>(i)
+        .expect("multispace0 never fails on complete input");
+    i
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionType {
+    Fast,
+    Smooth,
+}
+
+impl TryFrom<&[u8]> for TransitionType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value {
+            "fast" => TransitionType::Fast,
+            "smooth" => TransitionType::Smooth,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for TransitionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TransitionType::Fast => "fast",
+            TransitionType::Smooth => "smooth",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumProp for TransitionType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Prop> {
+        let (i, (_, _, val)) =
+            tuple((tag("transition"), space1, maybe_bracketed(transition_val)))(i)?;
+        Ok((i, Prop::Transition(val)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorType {
+    Coffee,
+    Water,
+}
+
+impl TryFrom<&[u8]> for SensorType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value {
+            "coffee" => SensorType::Coffee,
+            "water" => SensorType::Water,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for SensorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            SensorType::Coffee => "coffee",
+            SensorType::Water => "water",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumProp for SensorType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Prop> {
+        let (i, (_, _, val)) = tuple((tag("sensor"), space1, maybe_bracketed(sensor_val)))(i)?;
+        Ok((i, Prop::Sensor(val)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PumpType {
+    Flow,
+    Pressure,
+}
+
+impl TryFrom<&[u8]> for PumpType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value {
+            "flow" => PumpType::Flow,
+            "pressure" => PumpType::Pressure,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for PumpType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            PumpType::Flow => "flow",
+            PumpType::Pressure => "pressure",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumProp for PumpType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Prop> {
+        let (i, (_, _, val)) = tuple((tag("pump"), space1, maybe_bracketed(pump_val)))(i)?;
+        Ok((i, Prop::Pump(val)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitType {
+    PressureUnder,
+    PressureOver,
+    FlowUnder,
+    FlowOver,
+}
+
+impl TryFrom<&[u8]> for ExitType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value {
+            "pressure_under" => ExitType::PressureUnder,
+            "pressure_over" => ExitType::PressureOver,
+            "flow_under" => ExitType::FlowUnder,
+            "flow_over" => ExitType::FlowOver,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for ExitType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            ExitType::PressureUnder => "pressure_under",
+            ExitType::PressureOver => "pressure_over",
+            ExitType::FlowUnder => "flow_under",
+            ExitType::FlowOver => "flow_over",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumProp for ExitType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Prop> {
+        let (i, (_, _, val)) =
+            tuple((tag("exit_type"), space1, maybe_bracketed(exit_type_val)))(i)?;
+        Ok((i, Prop::ExitType(val)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step(pub Vec<Prop>);
+
+impl Step {
+    fn get(&self, prop_name: &str) -> Option<&Prop> {
+        self.0.iter().find(|prop| prop.key() == prop_name)
+    }
+
+    pub fn number(&self, prop_name: &str) -> Option<f32> {
+        self.get(prop_name).and_then(Prop::as_number)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self.get("name") {
+            Some(Prop::Name(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn exit_if(&self) -> Option<bool> {
+        match self.get("exit_if") {
+            Some(Prop::ExitIf(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn seconds(&self) -> Option<f32> {
+        self.number("seconds")
+    }
+
+    pub fn pump(&self) -> Option<PumpType> {
+        match self.get("pump") {
+            Some(Prop::Pump(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn transition(&self) -> Option<TransitionType> {
+        match self.get("transition") {
+            Some(Prop::Transition(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn sensor(&self) -> Option<SensorType> {
+        match self.get("sensor") {
+            Some(Prop::Sensor(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn exit_type(&self) -> Option<ExitType> {
+        match self.get("exit_type") {
+            Some(Prop::ExitType(v)) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+fn maybe_bracketed<'a, O>(
+    f: fn(&'a [u8]) -> IResult<&'a [u8], O>,
+) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], O> {
+    alt((delimited(tag("{"), f, tag("}")), f))
+}
+
+fn transition_val(i: &[u8]) -> IResult<&[u8], TransitionType> {
+    map_res(alt((tag("fast"), tag("smooth"))), TransitionType::try_from)(i)
+}
+
+fn sensor_val(i: &[u8]) -> IResult<&[u8], SensorType> {
+    map_res(alt((tag("coffee"), tag("water"))), SensorType::try_from)(i)
+}
+
+fn pump_val(i: &[u8]) -> IResult<&[u8], PumpType> {
+    map_res(alt((tag("flow"), tag("pressure"))), PumpType::try_from)(i)
+}
+
+fn exit_type_val(i: &[u8]) -> IResult<&[u8], ExitType> {
+    map_res(
+        alt((
+            tag("pressure_under"),
+            tag("pressure_over"),
+            tag("flow_under"),
+            tag("flow_over"),
+        )),
+        ExitType::try_from,
+    )(i)
+}
+
+fn bool_val(i: &[u8]) -> IResult<&[u8], bool> {
+    let (i, v) = alt((tag("0"), tag("1")))(i)?;
+    Ok((i, v == &b"1"[..]))
+}
+
+/// Signed decimal with an optional exponent, like Tcl reads it, and a comma for the point too.
+fn number_val(i: &[u8]) -> IResult<&[u8], Number> {
+    let point = || one_of(".,");
+    let mantissa = alt((
+        recognize(tuple((digit1, opt(tuple((point(), digit0)))))),
+        recognize(tuple((point(), digit1))),
+    ));
+    let exponent = tuple((one_of("eE"), opt(one_of("+-")), digit1));
+    let lexeme = recognize(tuple((opt(one_of("+-")), mantissa, opt(exponent))));
+    map_res(lexeme, |v: &[u8]| {
+        // NOTE: Only ASCII gets here, so the conversion is lossless
+        let lexeme = String::from_utf8_lossy(v).into_owned();
+        lexeme.replace(',', ".").parse().map(|value| Number {
+            value,
+            lexeme: Some(lexeme),
+        })
+    })(i)
+}
+
+fn prop_bool(name: &str, prop: fn(bool) -> Prop) -> impl Fn(&[u8]) -> IResult<&[u8], Prop> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, bool_val))(i)?;
+        Ok((i, prop(val)))
+    }
+}
+
+fn prop_number(name: &str, prop: fn(Number) -> Prop) -> impl Fn(&[u8]) -> IResult<&[u8], Prop> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, number_val))(i)?;
+        Ok((i, prop(val)))
+    }
+}
+
+fn prop_enum<E>() -> impl Fn(&[u8]) -> IResult<&[u8], Prop>
+where
+    E: ParsableEnumProp,
+{
+    |i: &[u8]| E::parse(i)
+}
+
+pub fn prop_string(name: &str, prop: fn(String) -> Prop) -> impl Fn(&[u8]) -> IResult<&[u8], Prop> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, word))(i)?;
+        Ok((i, prop(val)))
+    }
+}
+
+pub(crate) fn key_name(i: &[u8]) -> IResult<&[u8], &str> {
+    map_res(
+        take_while1(|c: u8| c.is_ascii_alphanumeric() || c == b'_'),
+        str::from_utf8,
+    )(i)
+}
+
+/// Keeps the value exactly as written, braces included, so it can be written back verbatim.
+fn raw_val(i: &[u8]) -> IResult<&[u8], String> {
+    let (i, v) = recognize(word)(i)?;
+    Ok((i, String::from_utf8_lossy(v).into_owned()))
+}
+
+/// Key and verbatim value of a key `expected` has no type for.
+fn unknown(
+    expected: fn(&str) -> Option<&'static str>,
+) -> impl Fn(&[u8]) -> IResult<&[u8], (String, String)> {
+    move |i: &[u8]| {
+        // NOTE: Known keys with a malformed value must fail instead of being swallowed here
+        let (i, (key, _, val)) = tuple((
+            verify(key_name, |key: &str| expected(key).is_none()),
+            space1,
+            raw_val,
+        ))(i)?;
+        Ok((i, (key.into(), val)))
+    }
+}
+
+fn prop_unknown(i: &[u8]) -> IResult<&[u8], Prop> {
+    map(unknown(prop_expected), Prop::Unknown)(i)
+}
+
+fn prop(i: &[u8]) -> IResult<&[u8], Prop> {
+    alt((
+        prop_bool("exit_if", Prop::ExitIf),
+        prop_number("flow", Prop::Flow),
+        prop_number("volume", Prop::Volume),
+        prop_number("max_flow_or_pressure_range", Prop::MaxFlowOrPressureRange),
+        prop_enum::<TransitionType>(),
+        prop_number("exit_flow_under", Prop::ExitFlowUnder),
+        prop_number("temperature", Prop::Temperature),
+        prop_string("name", Prop::Name),
+        prop_number("pressure", Prop::Pressure),
+        prop_enum::<SensorType>(),
+        prop_enum::<PumpType>(),
+        prop_enum::<ExitType>(),
+        prop_number("exit_flow_over", Prop::ExitFlowOver),
+        prop_number("exit_pressure_over", Prop::ExitPressureOver),
+        prop_number("max_flow_or_pressure", Prop::MaxFlowOrPressure),
+        prop_number("exit_pressure_under", Prop::ExitPressureUnder),
+        prop_number("seconds", Prop::Seconds),
+        prop_number("weight", Prop::Weight),
+        prop_unknown,
+    ))(i)
+}
+
+fn props(i: &[u8]) -> IResult<&[u8], Vec<Prop>> {
+    separated_list0(multispace1, prop)(i)
+}
+
+fn step(i: &[u8]) -> IResult<&[u8], Step> {
+    let (i, (_, _, v, _, _)) = tuple((tag("{"), multispace0, props, multispace0, tag("}")))(i)?;
+    Ok((i, Step(v)))
+}
+
+fn step_list(i: &[u8]) -> IResult<&[u8], Vec<Step>> {
+    many0(preceded(multispace0, step))(i)
+}
+
+fn prop_expected(key: &str) -> Option<&'static str> {
+    let expected = match key {
+        "exit_if" => "0 or 1",
+        "transition" => "one of fast, smooth",
+        "sensor" => "one of coffee, water",
+        "pump" => "one of flow, pressure",
+        "exit_type" => "one of pressure_under, pressure_over, flow_under, flow_over",
+        "name" => "a string",
+        "flow"
+        | "volume"
+        | "max_flow_or_pressure_range"
+        | "exit_flow_under"
+        | "temperature"
+        | "pressure"
+        | "exit_flow_over"
+        | "exit_pressure_over"
+        | "max_flow_or_pressure"
+        | "exit_pressure_under"
+        | "seconds"
+        | "weight" => "a number",
+        _ => return None,
+    };
+    Some(expected)
+}
+
+/// Pinpoints the property that stopped `step_list` at `rest`.
+fn step_error(input: &[u8], rest: &[u8]) -> ParseError {
+    if rest[0] != b'{' {
+        return ParseError::new(input, rest, None, "`{` to open a step");
+    }
+
+    let (after, _) = props(skip_space(&rest[1..])).expect("props never fails");
+    let after = skip_space(after);
+    if after.is_empty() {
+        return ParseError::new(input, after, None, "`}` to close the step");
+    }
+    match key_at(after) {
+        Some(key) => {
+            let expected = prop_expected(&key).unwrap_or("a value");
+            ParseError::new(input, after, Some(key), expected)
+        }
+        None => ParseError::new(input, after, None, "`}` to close the step"),
+    }
+}
+
+pub fn steps(input: &[u8]) -> Result<Vec<Step>, ParseError> {
+    let (rest, steps) = step_list(input).map_err(|_| step_error(input, input))?;
+    let rest = skip_space(rest);
+    if rest.is_empty() {
+        Ok(steps)
+    } else {
+        Err(step_error(input, rest))
+    }
+}
+
+trait ParsableEnumCommand {
+    fn parse(i: &[u8]) -> IResult<&[u8], Command>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BeverageType {
+    Calibrate,
+    Cleaning,
+    Espresso,
+    Filter,
+    Manual,
+    Pourover,
+    TeaPortafilter,
+}
+
+impl TryFrom<&[u8]> for BeverageType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value.to_lowercase().as_str() {
+            "calibrate" => BeverageType::Calibrate,
+            "cleaning" => BeverageType::Cleaning,
+            "espresso" => BeverageType::Espresso,
+            "filter" => BeverageType::Filter,
+            "manual" => BeverageType::Manual,
+            "pourover" => BeverageType::Pourover,
+            "tea" => BeverageType::TeaPortafilter,
+            "tea_portafilter" => BeverageType::TeaPortafilter,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for BeverageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            BeverageType::Calibrate => "calibrate",
+            BeverageType::Cleaning => "cleaning",
+            BeverageType::Espresso => "espresso",
+            BeverageType::Filter => "filter",
+            BeverageType::Manual => "manual",
+            BeverageType::Pourover => "pourover",
+            BeverageType::TeaPortafilter => "tea_portafilter",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumCommand for BeverageType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Command> {
+        let (i, (_, _, val)) = tuple((
+            tag("beverage_type"),
+            space1,
+            maybe_bracketed(beverage_type_val),
+        ))(i)?;
+        Ok((i, Command::BeverageType(val)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileType {
+    Settings1,
+    Settings2,
+    Settings2A,
+    Settings2B,
+    Settings2C,
+    Settings2C2,
+}
+
+impl TryFrom<&[u8]> for ProfileType {
+    type Error = UnexpectedValueError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = str::from_utf8(value).expect("should be converted");
+        let ret = match value {
+            "settings_1" => ProfileType::Settings1,
+            "settings_2" => ProfileType::Settings2,
+            "settings_2a" => ProfileType::Settings2A,
+            "settings_2b" => ProfileType::Settings2B,
+            "settings_2c" => ProfileType::Settings2C,
+            "settings_2c2" => ProfileType::Settings2C2,
+            _ => return Err(UnexpectedValueError(value.into())),
+        };
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for ProfileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            ProfileType::Settings1 => "settings_1",
+            ProfileType::Settings2 => "settings_2",
+            ProfileType::Settings2A => "settings_2a",
+            ProfileType::Settings2B => "settings_2b",
+            ProfileType::Settings2C => "settings_2c",
+            ProfileType::Settings2C2 => "settings_2c2",
+        };
+        f.write_str(value)
+    }
+}
+
+impl ParsableEnumCommand for ProfileType {
+    fn parse(i: &[u8]) -> IResult<&[u8], Command> {
+        let (i, (_, _, val)) = tuple((
+            tag("settings_profile_type"),
+            space1,
+            maybe_bracketed(profile_type_val),
+        ))(i)?;
+        Ok((i, Command::SettingsProfileType(val)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    AdvancedShot(String),
+    Author(String),
+    BeverageType(BeverageType),
+    EspressoDeclineTime(Number),
+    EspressoHoldTime(Number),
+    EspressoPressure(Number),
+    EspressoTemperature(Number),
+    EspressoTemperature0(Number),
+    EspressoTemperature1(Number),
+    EspressoTemperature2(Number),
+    EspressoTemperature3(Number),
+    EspressoTemperatureStepsEnabled(bool),
+    FinalDesiredShotVolume(Number),
+    FinalDesiredShotVolumeAdvanced(Number),
+    FinalDesiredShotVolumeAdvancedCountStart(Number),
+    FinalDesiredShotWeight(Number),
+    FinalDesiredShotWeightAdvanced(Number),
+    FlowProfileDecline(Number),
+    FlowProfileDeclineTime(Number),
+    FlowProfileHold(Number),
+    FlowProfileHoldTime(Number),
+    FlowProfileMinimumPressure(Number),
+    FlowProfilePreinfusion(Number),
+    FlowProfilePreinfusionTime(Number),
+    MaximumFlow(Number),
+    MaximumFlowRange(Number),
+    MaximumFlowRangeAdvanced(Number),
+    MaximumFlowRangeDefault(Number),
+    MaximumPressure(Number),
+    MaximumPressureRange(Number),
+    MaximumPressureRangeAdvanced(Number),
+    MaximumPressureRangeDefault(Number),
+    PreinfusionFlowRate(Number),
+    PreinfusionGuarantee(bool),
+    PreinfusionStopPressure(Number),
+    PreinfusionTime(Number),
+    PressureEnd(Number),
+    ProfileHide(bool),
+    ProfileLanguage(String),
+    ProfileNotes(String),
+    ProfileTitle(String),
+    SettingsProfileType(ProfileType),
+    TankDesiredWaterTemperature(Number),
+    WaterTemperature(Number),
+    BeanBrand(String),
+    BeanType(String),
+    GrinderDoseWeight(Number),
+    GrinderModel(String),
+    GrinderSetting(String),
+    Unknown((String, String)),
+}
+
+impl Command {
+    pub fn key(&self) -> &str {
+        match self {
+            Command::AdvancedShot(_) => "advanced_shot",
+            Command::Author(_) => "author",
+            Command::BeverageType(_) => "beverage_type",
+            Command::EspressoDeclineTime(_) => "espresso_decline_time",
+            Command::EspressoHoldTime(_) => "espresso_hold_time",
+            Command::EspressoPressure(_) => "espresso_pressure",
+            Command::EspressoTemperature(_) => "espresso_temperature",
+            Command::EspressoTemperature0(_) => "espresso_temperature_0",
+            Command::EspressoTemperature1(_) => "espresso_temperature_1",
+            Command::EspressoTemperature2(_) => "espresso_temperature_2",
+            Command::EspressoTemperature3(_) => "espresso_temperature_3",
+            Command::EspressoTemperatureStepsEnabled(_) => "espresso_temperature_steps_enabled",
+            Command::FinalDesiredShotVolume(_) => "final_desired_shot_volume",
+            Command::FinalDesiredShotVolumeAdvanced(_) => "final_desired_shot_volume_advanced",
+            Command::FinalDesiredShotVolumeAdvancedCountStart(_) => {
+                "final_desired_shot_volume_advanced_count_start"
+            }
+            Command::FinalDesiredShotWeight(_) => "final_desired_shot_weight",
+            Command::FinalDesiredShotWeightAdvanced(_) => "final_desired_shot_weight_advanced",
+            Command::FlowProfileDecline(_) => "flow_profile_decline",
+            Command::FlowProfileDeclineTime(_) => "flow_profile_decline_time",
+            Command::FlowProfileHold(_) => "flow_profile_hold",
+            Command::FlowProfileHoldTime(_) => "flow_profile_hold_time",
+            Command::FlowProfileMinimumPressure(_) => "flow_profile_minimum_pressure",
+            Command::FlowProfilePreinfusion(_) => "flow_profile_preinfusion",
+            Command::FlowProfilePreinfusionTime(_) => "flow_profile_preinfusion_time",
+            Command::MaximumFlow(_) => "maximum_flow",
+            Command::MaximumFlowRange(_) => "maximum_flow_range",
+            Command::MaximumFlowRangeAdvanced(_) => "maximum_flow_range_advanced",
+            Command::MaximumFlowRangeDefault(_) => "maximum_flow_range_default",
+            Command::MaximumPressure(_) => "maximum_pressure",
+            Command::MaximumPressureRange(_) => "maximum_pressure_range",
+            Command::MaximumPressureRangeAdvanced(_) => "maximum_pressure_range_advanced",
+            Command::MaximumPressureRangeDefault(_) => "maximum_pressure_range_default",
+            Command::PreinfusionFlowRate(_) => "preinfusion_flow_rate",
+            Command::PreinfusionGuarantee(_) => "preinfusion_guarantee",
+            Command::PreinfusionStopPressure(_) => "preinfusion_stop_pressure",
+            Command::PreinfusionTime(_) => "preinfusion_time",
+            Command::PressureEnd(_) => "pressure_end",
+            Command::ProfileHide(_) => "profile_hide",
+            Command::ProfileLanguage(_) => "profile_language",
+            Command::ProfileNotes(_) => "profile_notes",
+            Command::ProfileTitle(_) => "profile_title",
+            Command::SettingsProfileType(_) => "settings_profile_type",
+            Command::TankDesiredWaterTemperature(_) => "tank_desired_water_temperature",
+            Command::WaterTemperature(_) => "water_temperature",
+            Command::BeanBrand(_) => "bean_brand",
+            Command::BeanType(_) => "bean_type",
+            Command::GrinderDoseWeight(_) => "grinder_dose_weight",
+            Command::GrinderModel(_) => "grinder_model",
+            Command::GrinderSetting(_) => "grinder_setting",
+            Command::Unknown((name, _)) => name,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            Command::EspressoDeclineTime(v)
+            | Command::EspressoHoldTime(v)
+            | Command::EspressoPressure(v)
+            | Command::EspressoTemperature(v)
+            | Command::EspressoTemperature0(v)
+            | Command::EspressoTemperature1(v)
+            | Command::EspressoTemperature2(v)
+            | Command::EspressoTemperature3(v)
+            | Command::FinalDesiredShotVolume(v)
+            | Command::FinalDesiredShotVolumeAdvanced(v)
+            | Command::FinalDesiredShotVolumeAdvancedCountStart(v)
+            | Command::FinalDesiredShotWeight(v)
+            | Command::FinalDesiredShotWeightAdvanced(v)
+            | Command::FlowProfileDecline(v)
+            | Command::FlowProfileDeclineTime(v)
+            | Command::FlowProfileHold(v)
+            | Command::FlowProfileHoldTime(v)
+            | Command::FlowProfileMinimumPressure(v)
+            | Command::FlowProfilePreinfusion(v)
+            | Command::FlowProfilePreinfusionTime(v)
+            | Command::MaximumFlow(v)
+            | Command::MaximumFlowRange(v)
+            | Command::MaximumFlowRangeAdvanced(v)
+            | Command::MaximumFlowRangeDefault(v)
+            | Command::MaximumPressure(v)
+            | Command::MaximumPressureRange(v)
+            | Command::MaximumPressureRangeAdvanced(v)
+            | Command::MaximumPressureRangeDefault(v)
+            | Command::PreinfusionFlowRate(v)
+            | Command::PreinfusionStopPressure(v)
+            | Command::PreinfusionTime(v)
+            | Command::PressureEnd(v)
+            | Command::TankDesiredWaterTemperature(v)
+            | Command::WaterTemperature(v)
+            | Command::GrinderDoseWeight(v) => Some(v.value),
+            _ => None,
+        }
+    }
+}
+
+fn command_bool(name: &str, cmd: fn(bool) -> Command) -> impl Fn(&[u8]) -> IResult<&[u8], Command> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, bool_val))(i)?;
+        Ok((i, cmd(val)))
+    }
+}
+
+fn command_number(
+    name: &str,
+    cmd: fn(Number) -> Command,
+) -> impl Fn(&[u8]) -> IResult<&[u8], Command> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, number_val))(i)?;
+        Ok((i, cmd(val)))
+    }
+}
+
+fn command_string(
+    name: &str,
+    cmd: fn(String) -> Command,
+) -> impl Fn(&[u8]) -> IResult<&[u8], Command> {
+    let name = name.to_string();
+    move |i: &[u8]| {
+        let (i, (_, _, val)) = tuple((tag(name.as_bytes()), space1, word))(i)?;
+        Ok((i, cmd(val)))
+    }
+}
+
+fn beverage_type_val(i: &[u8]) -> IResult<&[u8], BeverageType> {
+    map_res(
+        alt((
+            tag_no_case("calibrate"),
+            tag_no_case("cleaning"),
+            tag_no_case("espresso"),
+            tag_no_case("filter"),
+            tag_no_case("manual"),
+            tag_no_case("pourover"),
+            tag_no_case("tea_portafilter"),
+            tag_no_case("tea"),
+        )),
+        BeverageType::try_from,
+    )(i)
+}
+
+fn profile_type_val(i: &[u8]) -> IResult<&[u8], ProfileType> {
+    map_res(
+        alt((
+            tag("settings_1"),
+            tag("settings_2c2"),
+            tag("settings_2a"),
+            tag("settings_2b"),
+            tag("settings_2c"),
+            tag("settings_2"),
+        )),
+        ProfileType::try_from,
+    )(i)
+}
+
+fn command_enum<E>() -> impl Fn(&[u8]) -> IResult<&[u8], Command>
+where
+    E: ParsableEnumCommand,
+{
+    |i: &[u8]| E::parse(i)
+}
+
+fn command_unknown(i: &[u8]) -> IResult<&[u8], Command> {
+    map(unknown(command_expected), Command::Unknown)(i)
+}
+
+fn command(i: &[u8]) -> IResult<&[u8], Command> {
+    // NOTE: Nested `alt` combinator was caused by nom's limitation of maximum 21 parsers.
+    alt((
+        alt((
+            command_string("advanced_shot", Command::AdvancedShot),
+            command_string("author", Command::Author),
+            command_enum::<BeverageType>(),
+            command_number("espresso_decline_time", Command::EspressoDeclineTime),
+            command_number("espresso_hold_time", Command::EspressoHoldTime),
+            command_number("espresso_pressure", Command::EspressoPressure),
+            command_number("espresso_temperature", Command::EspressoTemperature),
+            command_number("espresso_temperature_0", Command::EspressoTemperature0),
+            command_number("espresso_temperature_1", Command::EspressoTemperature1),
+            command_number("espresso_temperature_2", Command::EspressoTemperature2),
+            command_number("espresso_temperature_3", Command::EspressoTemperature3),
+            command_bool(
+                "espresso_temperature_steps_enabled",
+                Command::EspressoTemperatureStepsEnabled,
+            ),
+            command_number("final_desired_shot_volume", Command::FinalDesiredShotVolume),
+            command_number(
+                "final_desired_shot_volume_advanced",
+                Command::FinalDesiredShotVolumeAdvanced,
+            ),
+            command_number(
+                "final_desired_shot_volume_advanced_count_start",
+                Command::FinalDesiredShotVolumeAdvancedCountStart,
+            ),
+            command_number("final_desired_shot_weight", Command::FinalDesiredShotWeight),
+            command_number(
+                "final_desired_shot_weight_advanced",
+                Command::FinalDesiredShotWeightAdvanced,
+            ),
+            command_number("flow_profile_decline", Command::FlowProfileDecline),
+            command_number("flow_profile_decline_time", Command::FlowProfileDeclineTime),
+            command_number("flow_profile_hold", Command::FlowProfileHold),
+        )),
+        alt((
+            command_number("flow_profile_hold_time", Command::FlowProfileHoldTime),
+            command_number(
+                "flow_profile_minimum_pressure",
+                Command::FlowProfileMinimumPressure,
+            ),
+            command_number("flow_profile_preinfusion", Command::FlowProfilePreinfusion),
+            command_number(
+                "flow_profile_preinfusion_time",
+                Command::FlowProfilePreinfusionTime,
+            ),
+            command_number("maximum_flow", Command::MaximumFlow),
+            command_number("maximum_flow_range", Command::MaximumFlowRange),
+            command_number(
+                "maximum_flow_range_advanced",
+                Command::MaximumFlowRangeAdvanced,
+            ),
+            command_number(
+                "maximum_flow_range_default",
+                Command::MaximumFlowRangeDefault,
+            ),
+            command_number("maximum_pressure", Command::MaximumPressure),
+            command_number("maximum_pressure_range", Command::MaximumPressureRange),
+            command_number(
+                "maximum_pressure_range_advanced",
+                Command::MaximumPressureRangeAdvanced,
+            ),
+            command_number(
+                "maximum_pressure_range_default",
+                Command::MaximumPressureRangeDefault,
+            ),
+            command_number("preinfusion_flow_rate", Command::PreinfusionFlowRate),
+            command_bool("preinfusion_guarantee", Command::PreinfusionGuarantee),
+            command_number(
+                "preinfusion_stop_pressure",
+                Command::PreinfusionStopPressure,
+            ),
+            command_number("preinfusion_time", Command::PreinfusionTime),
+            command_number("pressure_end", Command::PressureEnd),
+            command_bool("profile_hide", Command::ProfileHide),
+            command_string("profile_language", Command::ProfileLanguage),
+            command_string("profile_notes", Command::ProfileNotes),
+        )),
+        alt((
+            command_string("profile_title", Command::ProfileTitle),
+            command_enum::<ProfileType>(),
+            command_number(
+                "tank_desired_water_temperature",
+                Command::TankDesiredWaterTemperature,
+            ),
+            command_number("water_temperature", Command::WaterTemperature),
+            command_string("bean_brand", Command::BeanBrand),
+            command_string("bean_type", Command::BeanType),
+            command_number("grinder_dose_weight", Command::GrinderDoseWeight),
+            command_string("grinder_model", Command::GrinderModel),
+            command_string("grinder_setting", Command::GrinderSetting),
+            command_unknown,
+        )),
+    ))(i)
+}
+
+fn command_expected(key: &str) -> Option<&'static str> {
+    let expected = match key {
+        "espresso_temperature_steps_enabled" | "preinfusion_guarantee" | "profile_hide" => "0 or 1",
+        "beverage_type" => {
+            "one of calibrate, cleaning, espresso, filter, manual, pourover, tea_portafilter"
+        }
+        "settings_profile_type" => {
+            "one of settings_1, settings_2, settings_2a, settings_2b, settings_2c, settings_2c2"
+        }
+        "advanced_shot" | "author" | "profile_language" | "profile_notes" | "profile_title"
+        | "bean_brand" | "bean_type" | "grinder_model" | "grinder_setting" => "a string",
+        "espresso_decline_time"
+        | "espresso_hold_time"
+        | "espresso_pressure"
+        | "espresso_temperature"
+        | "espresso_temperature_0"
+        | "espresso_temperature_1"
+        | "espresso_temperature_2"
+        | "espresso_temperature_3"
+        | "final_desired_shot_volume"
+        | "final_desired_shot_volume_advanced"
+        | "final_desired_shot_volume_advanced_count_start"
+        | "final_desired_shot_weight"
+        | "final_desired_shot_weight_advanced"
+        | "flow_profile_decline"
+        | "flow_profile_decline_time"
+        | "flow_profile_hold"
+        | "flow_profile_hold_time"
+        | "flow_profile_minimum_pressure"
+        | "flow_profile_preinfusion"
+        | "flow_profile_preinfusion_time"
+        | "maximum_flow"
+        | "maximum_flow_range"
+        | "maximum_flow_range_advanced"
+        | "maximum_flow_range_default"
+        | "maximum_pressure"
+        | "maximum_pressure_range"
+        | "maximum_pressure_range_advanced"
+        | "maximum_pressure_range_default"
+        | "preinfusion_flow_rate"
+        | "preinfusion_stop_pressure"
+        | "preinfusion_time"
+        | "pressure_end"
+        | "tank_desired_water_temperature"
+        | "water_temperature"
+        | "grinder_dose_weight" => "a number",
+        _ => return None,
+    };
+    Some(expected)
+}
+
+pub fn profile(input: &[u8]) -> Result<Vec<Command>, ParseError> {
+    let mut commands = vec![];
+    let mut i = skip_space(input);
+    while !i.is_empty() {
+        let (rest, cmd) = command(i).map_err(|_| {
+            let key = key_at(i);
+            let expected = key.as_deref().map_or("a profile key", |key| {
+                command_expected(key).unwrap_or("a value")
+            });
+            ParseError::new(input, i, key, expected)
+        })?;
+
+        // NOTE: Steps are validated here so errors point into the file, not into the value.
+        if let Command::AdvancedShot(data) = &cmd {
+            let mut end = input.len() - rest.len();
+            if input[..end].ends_with(b"}") {
+                end -= 1;
+            }
+            let start = end.saturating_sub(data.len());
+            let base = Some(start).filter(|&start| &input[start..end] == data.as_bytes());
+            steps(data.as_bytes()).map_err(|e| e.relocate(input, base, i, "advanced_shot"))?;
+        }
+
+        commands.push(cmd);
+        i = skip_space(rest);
+    }
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::error::{Error, ErrorKind};
+
+    use super::*;
+
+    #[test]
+    fn test_number_val() {
+        let number = |i: &'static [u8]| {
+            let (rest, v) = number_val(i).unwrap();
+            (rest, v.value, v.to_string())
+        };
+        assert_eq!(number(b"8;"), (&b";"[..], 8.0, "8".into()));
+        assert_eq!(number(b"80;"), (&b";"[..], 80.0, "80".into()));
+        assert_eq!(number(b"8.;"), (&b";"[..], 8.0, "8.".into()));
+        assert_eq!(number(b"8.0;"), (&b";"[..], 8.0, "8.0".into()));
+        assert_eq!(number(b".8;"), (&b";"[..], 0.8, ".8".into()));
+        assert_eq!(number(b"-1 "), (&b" "[..], -1.0, "-1".into()));
+        assert_eq!(number(b"+2.5}"), (&b"}"[..], 2.5, "+2.5".into()));
+        assert_eq!(number(b"1e-3;"), (&b";"[..], 0.001, "1e-3".into()));
+        assert_eq!(number(b"70000"), (&b""[..], 70000.0, "70000".into()));
+        assert_eq!(
+            number(b"-3.885780586188048e-16 "),
+            (&b" "[..], -3.8857806e-16, "-3.885780586188048e-16".into())
+        );
+        assert_eq!(number(b"8,5;"), (&b";"[..], 8.5, "8,5".into()));
+        // NOTE: An exponent without digits isn't part of the number
+        assert_eq!(number(b"2e;"), (&b"e;"[..], 2.0, "2".into()));
+        assert!(number_val(b"-;").is_err());
+        assert!(number_val(b".;").is_err());
+    }
+
+    #[test]
+    fn test_number_lexeme() {
+        let n = Number::from(8.5);
+        assert_eq!(n.lexeme(), "8.5");
+        let (_, parsed) = number_val(b"8.50").unwrap();
+        assert_eq!(parsed.lexeme(), "8.50");
+        assert_eq!(parsed, n);
+    }
+
+    #[test]
+    fn test_word() {
+        assert_eq!(word(b"Fill ;"), Ok((&b" ;"[..], "Fill".into())));
+        assert_eq!(word(b"Fill\n;"), Ok((&b"\n;"[..], "Fill".into())));
+        assert_eq!(
+            word(b"{Pressure Up};"),
+            Ok((&b";"[..], "Pressure Up".into()))
+        );
+        assert_eq!(
+            word(b"{New\n\"Line\"\n\nSupported \n};"),
+            Ok((&b";"[..], "New\n\"Line\"\n\nSupported \n".into()))
+        );
+        assert_eq!(
+            word(b"{{exit_if 0 flow 5.0} {volume 200.0 name {Rust}}};"),
+            Ok((
+                &b";"[..],
+                "{exit_if 0 flow 5.0} {volume 200.0 name {Rust}}".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_transition_val() {
+        assert_eq!(
+            transition_val(b"fast;"),
+            Ok((&b";"[..], TransitionType::Fast))
+        );
+        assert_eq!(
+            transition_val(b"smooth;"),
+            Ok((&b";"[..], TransitionType::Smooth))
+        );
+        assert_eq!(
+            transition_val(b"slow;"),
+            Err(nom::Err::Error(Error::new(&b"slow;"[..], ErrorKind::Tag)))
+        );
+    }
+
+    #[test]
+    fn test_prop_bool() {
+        let prop_exit_if = prop_bool("exit_if", Prop::ExitIf);
+        assert_eq!(
+            prop_exit_if(b"exit_if 1;"),
+            Ok((&b";"[..], Prop::ExitIf(true)))
+        );
+        assert_eq!(
+            prop_exit_if(b"exit_if 0;"),
+            Ok((&b";"[..], Prop::ExitIf(false)))
+        );
+        assert_eq!(
+            prop_exit_if(b"exit_if x;"),
+            Err(nom::Err::Error(Error::new(&b"x;"[..], ErrorKind::Tag)))
+        );
+    }
+
+    #[test]
+    fn test_prop_enum() {
+        let prop_transition = prop_enum::<TransitionType>();
+        assert_eq!(
+            prop_transition(b"transition fast;"),
+            Ok((&b";"[..], Prop::Transition(TransitionType::Fast)))
+        );
+        assert_eq!(
+            prop_transition(b"transition smooth;"),
+            Ok((&b";"[..], Prop::Transition(TransitionType::Smooth)))
+        );
+        assert_eq!(
+            prop_transition(b"transition smooooch;"),
+            Err(nom::Err::Error(Error::new(
+                &b"smooooch;"[..],
+                ErrorKind::Tag
+            )))
+        );
+    }
+
+    #[test]
+    fn test_prop_string() {
+        let prop_name = prop_string("name", Prop::Name);
+        assert_eq!(
+            prop_name(b"name Fill\n"),
+            Ok((&b"\n"[..], Prop::Name("Fill".into())))
+        );
+        assert_eq!(
+            prop_name(b"name {Pressure Up}\n"),
+            Ok((&b"\n"[..], Prop::Name("Pressure Up".into())))
+        );
+        assert_eq!(
+            prop_name(b"name {}\n"),
+            Ok((&b"\n"[..], Prop::Name("".into())))
+        );
+    }
+
+    #[test]
+    fn test_prop() {
+        assert_eq!(prop(b"flow 8;"), Ok((&b";"[..], Prop::Flow(8.0.into()))));
+        assert_eq!(
+            prop(b"volume 100;"),
+            Ok((&b";"[..], Prop::Volume(100.0.into())))
+        );
+        assert_eq!(
+            prop(b"exit_pressure_over 1.5;"),
+            Ok((&b";"[..], Prop::ExitPressureOver(1.5.into())))
+        );
+    }
+
+    #[test]
+    fn test_step_inner() {
+        let payload = include_str!("../fixtures/step.inner");
+        assert_eq!(
+            props(payload.as_bytes()),
+            Ok((
+                &b""[..],
+                vec![
+                    Prop::ExitIf(true),
+                    Prop::Flow(8.0.into()),
+                    Prop::Volume(100.0.into()),
+                    Prop::MaxFlowOrPressureRange(0.6.into()),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::ExitFlowUnder(0.0.into()),
+                    Prop::Temperature(94.0.into()),
+                    Prop::Name("Fill".into()),
+                    Prop::Pressure(2.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Pump(PumpType::Pressure),
+                    Prop::ExitType(ExitType::PressureOver),
+                    Prop::ExitFlowOver(6.0.into()),
+                    Prop::ExitPressureOver(1.5.into()),
+                    Prop::MaxFlowOrPressure(0.0.into()),
+                    Prop::ExitPressureUnder(0.0.into()),
+                    Prop::Seconds(25.0.into()),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_step_outer() {
+        let payload = include_str!("../fixtures/step.outer");
+        assert_eq!(
+            step(payload.as_bytes()),
+            Ok((
+                &b""[..],
+                Step(vec![
+                    Prop::ExitIf(true),
+                    Prop::Flow(8.0.into()),
+                    Prop::Volume(100.0.into()),
+                    Prop::MaxFlowOrPressureRange(0.6.into()),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::ExitFlowUnder(0.0.into()),
+                    Prop::Temperature(94.0.into()),
+                    Prop::Name("Fill".into()),
+                    Prop::Pressure(2.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Pump(PumpType::Pressure),
+                    Prop::ExitType(ExitType::PressureOver),
+                    Prop::ExitFlowOver(6.0.into()),
+                    Prop::ExitPressureOver(1.5.into()),
+                    Prop::MaxFlowOrPressure(0.0.into()),
+                    Prop::ExitPressureUnder(0.0.into()),
+                    Prop::Seconds(25.0.into()),
+                ])
+            ))
+        );
+    }
+
+    #[test]
+    fn test_steps_inner() {
+        assert_eq!(
+            steps(&b"{volume 100}\n{flow 8}\n"[..]),
+            Ok(vec![
+                Step(vec![Prop::Volume(100.0.into()),]),
+                Step(vec![Prop::Flow(8.0.into())])
+            ])
+        );
+
+        let payload = include_str!("../fixtures/steps.inner");
+        assert_eq!(
+            steps(payload.as_bytes()),
+            Ok(vec![
+                Step(vec![
+                    Prop::ExitIf(true),
+                    Prop::Flow(8.0.into()),
+                    Prop::Volume(100.0.into()),
+                    Prop::MaxFlowOrPressureRange(0.6.into()),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::ExitFlowUnder(0.0.into()),
+                    Prop::Temperature(94.0.into()),
+                    Prop::Name("Fill".into()),
+                    Prop::Pressure(2.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Pump(PumpType::Pressure),
+                    Prop::ExitType(ExitType::PressureOver),
+                    Prop::ExitFlowOver(6.0.into()),
+                    Prop::ExitPressureOver(1.5.into()),
+                    Prop::MaxFlowOrPressure(0.0.into()),
+                    Prop::ExitPressureUnder(0.0.into()),
+                    Prop::Seconds(25.0.into()),
+                ]),
+                Step(vec![
+                    Prop::ExitIf(false),
+                    Prop::Volume(100.0.into()),
+                    Prop::MaxFlowOrPressureRange(0.6.into()),
+                    Prop::Transition(TransitionType::Fast),
+                    Prop::ExitFlowUnder(0.0.into()),
+                    Prop::Temperature(93.0.into()),
+                    Prop::Weight(0.0.into()),
+                    Prop::Name("Pressure Up".into()),
+                    Prop::Pressure(9.0.into()),
+                    Prop::Sensor(SensorType::Coffee),
+                    Prop::Pump(PumpType::Pressure),
+                    Prop::ExitFlowOver(6.0.into()),
+                    Prop::ExitPressureOver(11.0.into()),
+                    Prop::MaxFlowOrPressure(0.0.into()),
+                    Prop::Seconds(4.0.into()),
+                    Prop::ExitPressureUnder(0.0.into()),
+                ])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_steps_error() {
+        assert_eq!(
+            steps(b"{volume 100}\n{flow 8 transition slow}\n"),
+            Err(ParseError {
+                offset: 21,
+                line: 2,
+                column: 9,
+                key: Some("transition".into()),
+                expected: "one of fast, smooth".into(),
+                unconsumed: 17,
+            })
+        );
+        assert_eq!(
+            steps(b"{volume 100} {flow 8"),
+            Err(ParseError {
+                offset: 20,
+                line: 1,
+                column: 21,
+                key: None,
+                expected: "`}` to close the step".into(),
+                unconsumed: 0,
+            })
+        );
+        assert_eq!(
+            steps(b"volume 100"),
+            Err(ParseError {
+                offset: 0,
+                line: 1,
+                column: 1,
+                key: None,
+                expected: "`{` to open a step".into(),
+                unconsumed: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(
+            command(b"author Decent"),
+            Ok((&b""[..], Command::Author("Decent".into())))
+        );
+        assert_eq!(
+            command(b"author {Decent}"),
+            Ok((&b""[..], Command::Author("Decent".into())))
+        );
+
+        assert_eq!(
+            command(b"beverage_type filter"),
+            Ok((&b""[..], Command::BeverageType(BeverageType::Filter)))
+        );
+        assert_eq!(
+            command(b"beverage_type cleaning"),
+            Ok((&b""[..], Command::BeverageType(BeverageType::Cleaning)))
+        );
+        assert_eq!(
+            command(b"beverage_type Cleaning"),
+            Ok((&b""[..], Command::BeverageType(BeverageType::Cleaning)))
+        );
+        assert_eq!(
+            command(b"beverage_type tea"),
+            Ok((
+                &b""[..],
+                Command::BeverageType(BeverageType::TeaPortafilter)
+            ))
+        );
+        assert_eq!(
+            command(b"beverage_type tea_portafilter"),
+            Ok((
+                &b""[..],
+                Command::BeverageType(BeverageType::TeaPortafilter)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_profile_file() {
+        let payload = include_str!("../fixtures/profile.tcl");
+        assert_eq!(
+            profile(payload.as_bytes()),
+            Ok(vec![
+                Command::AdvancedShot(
+                    "{exit_if 0 flow 4.0} {temperature 98.00 name {3 mL/s} seconds 60.00}".into()
+                ),
+                Command::Author("Decent".into()),
+                Command::BeverageType(BeverageType::Pourover),
+                Command::EspressoDeclineTime(0.0.into()),
+                Command::EspressoHoldTime(25.0.into()),
+                Command::EspressoPressure(8.6.into()),
+                Command::EspressoTemperature(98.0.into()),
+                Command::EspressoTemperature0(90.0.into()),
+                Command::EspressoTemperature1(88.0.into()),
+                Command::EspressoTemperature2(88.0.into()),
+                Command::EspressoTemperature3(88.0.into()),
+                Command::EspressoTemperatureStepsEnabled(true),
+                Command::FinalDesiredShotVolume(36.0.into()),
+                Command::FinalDesiredShotVolumeAdvanced(0.0.into()),
+                Command::FinalDesiredShotVolumeAdvancedCountStart(0.0.into()),
+                Command::FinalDesiredShotWeight(100.0.into()),
+                Command::FinalDesiredShotWeightAdvanced(100.0.into()),
+                Command::FlowProfileDecline(1.2.into()),
+                Command::FlowProfileDeclineTime(17.0.into()),
+                Command::FlowProfileHold(2.0.into()),
+                Command::FlowProfileHoldTime(8.0.into()),
+                Command::FlowProfileMinimumPressure(4.0.into()),
+                Command::FlowProfilePreinfusion(4.0.into()),
+                Command::FlowProfilePreinfusionTime(5.0.into()),
+                Command::MaximumFlow(0.0.into()),
+                Command::MaximumFlowRangeAdvanced(1.0.into()),
+                Command::MaximumFlowRangeDefault(1.0.into()),
+                Command::MaximumPressure(0.0.into()),
+                Command::MaximumPressureRangeAdvanced(0.9.into()),
+                Command::MaximumPressureRangeDefault(0.9.into()),
+                Command::PreinfusionFlowRate(4.0.into()),
+                Command::PreinfusionStopPressure(4.0.into()),
+                Command::PreinfusionTime(0.0.into()),
+                Command::PressureEnd(6.0.into()),
+                Command::ProfileHide(true),
+                Command::ProfileLanguage("en".into()),
+                Command::ProfileNotes("first line\n\nafter blank line\nlast line".into()),
+                Command::ProfileTitle("Filter 2.1".into()),
+                Command::SettingsProfileType(ProfileType::Settings2C),
+                Command::TankDesiredWaterTemperature(0.0.into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_prop_unknown() {
+        assert_eq!(
+            prop(b"popup {Hello world} rest"),
+            Ok((
+                &b" rest"[..],
+                Prop::Unknown(("popup".into(), "{Hello world}".into()))
+            ))
+        );
+        assert_eq!(
+            step(b"{flow 2 limiter_value 0.8}"),
+            Ok((
+                &b""[..],
+                Step(vec![
+                    Prop::Flow(2.0.into()),
+                    Prop::Unknown(("limiter_value".into(), "0.8".into())),
+                ])
+            ))
+        );
+        assert!(prop(b"pump air").is_err());
+    }
+
+    #[test]
+    fn test_profile_unknown_keys() {
+        assert_eq!(
+            profile(b"author Decent\nprofile_video_help {https://youtu.be/x}\nlegacy_flag 1\nprofile_title Hi\n"),
+            Ok(vec![
+                Command::Author("Decent".into()),
+                Command::Unknown(("profile_video_help".into(), "{https://youtu.be/x}".into())),
+                Command::Unknown(("legacy_flag".into(), "1".into())),
+                Command::ProfileTitle("Hi".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_profile_bracketed_enum() {
+        assert_eq!(
+            profile(b"beverage_type {espresso}\nsettings_profile_type {settings_2c}\n"),
+            Ok(vec![
+                Command::BeverageType(BeverageType::Espresso),
+                Command::SettingsProfileType(ProfileType::Settings2C),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_profile_tcl_words() {
+        let payload = r#"profile_title "Blooming \"Light\""
+profile_notes {Grind finer.
+Use a \} brace and {nested {braces}}}
+author Café\ Team
+advanced_shot {{name "Pre \{1\}" flow 4 seconds 10} {name Pour\ in pressure 9}}
+"#;
+        let commands = profile(payload.as_bytes()).unwrap();
+        assert_eq!(
+            commands[..3],
+            [
+                Command::ProfileTitle("Blooming \"Light\"".into()),
+                Command::ProfileNotes("Grind finer.\nUse a \\} brace and {nested {braces}}".into()),
+                Command::Author("Café Team".into()),
+            ]
+        );
+        let data = match &commands[3] {
+            Command::AdvancedShot(data) => data,
+            cmd => panic!("unexpected {:?}", cmd),
+        };
+        let names = steps(data.as_bytes())
+            .unwrap()
+            .iter()
+            .map(|step| step.name().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Pre {1}", "Pour in"]);
+    }
+
+    #[test]
+    fn test_profile_error() {
+        let err = profile(b"author Decent\nbeverage_type coffee\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                offset: 14,
+                line: 2,
+                column: 1,
+                key: Some("beverage_type".into()),
+                expected:
+                    "one of calibrate, cleaning, espresso, filter, manual, pourover, tea_portafilter"
+                        .into(),
+                unconsumed: 21,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 1: expected one of calibrate, cleaning, espresso, filter, manual, pourover, tea_portafilter for `beverage_type`"
+        );
+    }
+
+    #[test]
+    fn test_profile_error_in_advanced_shot() {
+        assert_eq!(
+            profile(b"author Decent\nadvanced_shot {{flow 8} {pump air}}\n"),
+            Err(ParseError {
+                offset: 39,
+                line: 2,
+                column: 26,
+                key: Some("pump".into()),
+                expected: "one of flow, pressure".into(),
+                unconsumed: 11,
+            })
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use crate::model::{ExitCondition, Limiter, StepSpec};
+use crate::parser::{ExitType, PumpType, SensorType, Step, TransitionType};
+use crate::shot::ShotSeries;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnalyzedProfile {
+    pub temperature: Vec<TemperatureSegment>,
+    /// Desired water tank temperature, `None` when the tank heater is off.
+    pub tank_temperature: Option<f64>,
+    pub pressure: PositionList,
+    pub flow: PositionList,
+    /// Pressure limits set on flow-pump steps.
+    pub pressure_limits: Vec<LimitSegment>,
+    /// Flow limits set on pressure-pump steps.
+    pub flow_limits: Vec<LimitSegment>,
+    pub steps: Vec<StepInfo>,
+    pub elapsed_time: f64,
+    /// Curves measured while pulling a shot with the profile, drawn over the targets.
+    pub shot: Option<ShotSeries>,
+}
+
+pub type PositionList = Vec<(f64, f64, f64, f64)>;
+
+impl AnalyzedProfile {
+    /// Tank temperature is a profile setting, steps don't carry it.
+    pub fn with_tank_temperature(mut self, tank: Option<f32>) -> Self {
+        self.tank_temperature = tank.filter(|t| *t > 0.).map(f64::from);
+        self
+    }
+
+    /// Shots often run longer or shorter than the profile, so the timeline covers both.
+    pub fn with_shot(mut self, shot: Option<ShotSeries>) -> Self {
+        if let Some(shot) = &shot {
+            self.elapsed_time = self.elapsed_time.max(shot.duration());
+        }
+        self.shot = shot;
+        self
+    }
+
+    /// Targets at `time`, `None` for a curve that isn't driven at that instant.
+    pub fn readout(&self, time: f64) -> Readout {
+        let temperature = self.temperature.iter().map(|segment| &segment.position);
+        Readout {
+            time,
+            pressure: interpolate(self.pressure.iter(), time),
+            flow: interpolate(self.flow.iter(), time),
+            temperature: interpolate(temperature, time),
+            step: self
+                .steps
+                .iter()
+                .position(|step| step.start <= time && time < step.end)
+                .or_else(|| {
+                    // NOTE: The end of the last step belongs to it rather than to nothing
+                    self.steps
+                        .iter()
+                        .rposition(|step| step.start < step.end && step.end == time)
+                }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Readout {
+    pub time: f64,
+    pub pressure: Option<f64>,
+    pub flow: Option<f64>,
+    pub temperature: Option<f64>,
+    /// Index into `AnalyzedProfile::steps`.
+    pub step: Option<usize>,
+}
+
+fn interpolate<'a>(
+    positions: impl Iterator<Item = &'a (f64, f64, f64, f64)>,
+    time: f64,
+) -> Option<f64> {
+    // NOTE: Vertical segments are jumps between steps, the flat ones around them hold the value
+    positions
+        .filter(|(x1, _, x2, _)| x1 < x2 && *x1 <= time && time <= *x2)
+        .last()
+        .map(|(x1, y1, x2, y2)| y1 + (y2 - y1) * (time - x1) / (x2 - x1))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TemperatureSegment {
+    pub position: (f64, f64, f64, f64),
+    /// Where the target is measured, the water sensor reads hotter than the puck.
+    pub sensor: SensorType,
+}
+
+/// Limiter of a step over its time span, in the unit of the limited quantity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LimitSegment {
+    pub start: f64,
+    pub end: f64,
+    pub value: f64,
+    pub range: f64,
+}
+
+/// Where a step sits on the timeline and what can end it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepInfo {
+    pub name: String,
+    pub start: f64,
+    /// End of the step when it runs for its full `seconds`.
+    pub end: f64,
+    pub exits: Vec<StepExit>,
+    /// Whether an exit condition will probably end the step before `end`.
+    pub exits_early: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepExit {
+    pub kind: ExitKind,
+    pub value: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitKind {
+    PressureOver,
+    PressureUnder,
+    FlowOver,
+    FlowUnder,
+    Weight,
+    Volume,
+}
+
+impl From<ExitType> for ExitKind {
+    fn from(value: ExitType) -> Self {
+        match value {
+            ExitType::PressureOver => ExitKind::PressureOver,
+            ExitType::PressureUnder => ExitKind::PressureUnder,
+            ExitType::FlowOver => ExitKind::FlowOver,
+            ExitType::FlowUnder => ExitKind::FlowUnder,
+        }
+    }
+}
+
+pub fn analyze(steps: &[Step]) -> AnalyzedProfile {
+    let mut temperature_pos: Vec<TemperatureSegment> = vec![];
+    let mut last_temperature_pos: Option<(f64, f64, f64, f64)> = None;
+
+    let mut pressure_pos: PositionList = vec![];
+    let mut last_pressure_pos: Option<(f64, f64, f64, f64)> = None;
+
+    let mut flow_pos: PositionList = vec![];
+    let mut last_flow_pos: Option<(f64, f64, f64, f64)> = None;
+
+    let mut elapsed_time = 0f64;
+    let mut prev_pump = None;
+    let mut prev_exit_flow: Option<f32> = None;
+    let mut pressure_limits = vec![];
+    let mut flow_limits = vec![];
+    let mut infos = vec![];
+
+    for step in steps.iter() {
+        let spec = StepSpec::from(step);
+        let duration = spec.seconds as f64;
+        let transition = spec.transition;
+        let pump = spec.pump;
+
+        if let Some(t) = spec.temperature {
+            let t = t as f64;
+            let mut push = |position| {
+                temperature_pos.push(TemperatureSegment {
+                    position,
+                    sensor: spec.sensor,
+                })
+            };
+            match (last_temperature_pos, transition) {
+                (Some((.., prev_t)), TransitionType::Fast) => {
+                    push((elapsed_time, prev_t, elapsed_time, t));
+                    push((elapsed_time, t, elapsed_time + duration, t));
+                }
+                (Some((.., prev_t)), TransitionType::Smooth) => {
+                    push((elapsed_time, prev_t, elapsed_time + duration, t));
+                }
+                (None, _) => push((elapsed_time, t, elapsed_time + duration, t)),
+            }
+            last_temperature_pos = temperature_pos.last().map(|s| s.position);
+        }
+
+        if pump == PumpType::Pressure {
+            if let (Some(PumpType::Flow), Some((.., px, py))) = (prev_pump, last_flow_pos) {
+                flow_pos.push((px, py, px, 0.));
+                last_flow_pos = Some(*flow_pos.last().unwrap());
+            }
+
+            let v = spec.pressure as f64;
+            if let Some((.., prev_v)) = last_pressure_pos {
+                match transition {
+                    TransitionType::Fast => {
+                        pressure_pos.push((elapsed_time, prev_v, elapsed_time, v));
+                        pressure_pos.push((elapsed_time, v, elapsed_time + duration, v));
+                    }
+                    TransitionType::Smooth => {
+                        pressure_pos.push((elapsed_time, prev_v, elapsed_time + duration, v));
+                    }
+                }
+            } else {
+                pressure_pos.push((elapsed_time, 0., elapsed_time, v));
+                pressure_pos.push((elapsed_time, v, elapsed_time + duration, v));
+            }
+
+            last_pressure_pos = Some(*pressure_pos.last().unwrap());
+        }
+
+        if pump == PumpType::Flow {
+            if let (Some(PumpType::Pressure), Some((.., px, py))) = (prev_pump, last_pressure_pos) {
+                pressure_pos.push((px, py, px, 0.));
+                last_pressure_pos = Some(*pressure_pos.last().unwrap());
+            }
+
+            let v = spec.flow as f64;
+            if let Some((.., prev_v)) = last_flow_pos {
+                let mut prev_v = prev_v;
+                if let Some(f) = prev_exit_flow {
+                    flow_pos.push((elapsed_time, prev_v, elapsed_time, f as f64));
+                    prev_v = f as f64;
+                }
+
+                match transition {
+                    TransitionType::Fast => {
+                        flow_pos.push((elapsed_time, prev_v, elapsed_time, v));
+                        flow_pos.push((elapsed_time, v, elapsed_time + duration, v));
+                    }
+                    TransitionType::Smooth => {
+                        flow_pos.push((elapsed_time, prev_v, elapsed_time + duration, v));
+                    }
+                }
+            } else {
+                flow_pos.push((elapsed_time, 0., elapsed_time, v));
+                flow_pos.push((elapsed_time, v, elapsed_time + duration, v));
+            }
+
+            last_flow_pos = Some(*flow_pos.last().unwrap());
+        }
+
+        if let Some(Limiter { value, range }) = spec.limiter {
+            let segment = LimitSegment {
+                start: elapsed_time,
+                end: elapsed_time + duration,
+                value: value as f64,
+                range: range as f64,
+            };
+            match pump {
+                PumpType::Flow => pressure_limits.push(segment),
+                PumpType::Pressure => flow_limits.push(segment),
+            }
+        }
+
+        infos.push(step_info(&spec, elapsed_time));
+        elapsed_time += duration;
+        prev_pump = Some(pump);
+        prev_exit_flow = match spec.exit {
+            Some(ExitCondition {
+                kind: ExitType::FlowOver | ExitType::FlowUnder,
+                value,
+            }) => Some(value),
+            _ => None,
+        };
+    }
+
+    AnalyzedProfile {
+        temperature: temperature_pos,
+        tank_temperature: None,
+        pressure: pressure_pos,
+        flow: flow_pos,
+        pressure_limits,
+        flow_limits,
+        steps: infos,
+        elapsed_time,
+        shot: None,
+    }
+}
+
+fn step_info(spec: &StepSpec, start: f64) -> StepInfo {
+    let mut exits = vec![];
+    if let Some(exit) = spec.exit {
+        exits.push(StepExit {
+            kind: exit.kind.into(),
+            value: exit.value as f64,
+        });
+    }
+    if let Some(v) = spec.weight {
+        exits.push(StepExit {
+            kind: ExitKind::Weight,
+            value: v as f64,
+        });
+    }
+    if let Some(v) = spec.volume {
+        exits.push(StepExit {
+            kind: ExitKind::Volume,
+            value: v as f64,
+        });
+    }
+
+    StepInfo {
+        name: spec.name.clone(),
+        start,
+        end: start + spec.seconds as f64,
+        exits,
+        exits_early: exits_early(spec),
+    }
+}
+
+fn exits_early(spec: &StepSpec) -> bool {
+    let exit = match spec.exit {
+        Some(exit) => exit,
+        None => return false,
+    };
+    match (exit.kind, spec.pump) {
+        // NOTE: Pressure builds up against the puck while the pump pushes water at a set flow
+        (ExitType::PressureOver, PumpType::Flow) => true,
+        (ExitType::PressureOver, PumpType::Pressure) => spec.pressure >= exit.value,
+        (ExitType::PressureUnder, PumpType::Pressure) => spec.pressure < exit.value,
+        (ExitType::FlowOver, PumpType::Flow) => spec.flow >= exit.value,
+        (ExitType::FlowUnder, PumpType::Flow) => spec.flow < exit.value,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_step_with_missing_keys() {
+        let steps = crate::parser::steps(
+            b"{name preinfusion flow 4 seconds 10 pump flow} {name pour pressure 9 seconds 20}",
+        )
+        .unwrap();
+        let profile = analyze(&steps);
+        assert_eq!(profile.elapsed_time, 30.);
+        assert_eq!(
+            profile.pressure,
+            vec![(10., 0., 10., 9.), (10., 9., 30., 9.)]
+        );
+        assert_eq!(
+            profile.flow,
+            vec![(0., 0., 0., 4.), (0., 4., 10., 4.), (10., 4., 10., 0.)]
+        );
+        assert!(profile.temperature.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_steps() {
+        let steps = crate::parser::steps(
+            b"{name preinfusion flow 4 seconds 20 pump flow exit_if 1 exit_type pressure_over \
+              exit_pressure_over 4 volume 100} \
+              {name pour pressure 9 seconds 30 pump pressure exit_if 1 exit_type flow_over \
+              exit_flow_over 3 weight 36}",
+        )
+        .unwrap();
+        let profile = analyze(&steps);
+        assert_eq!(
+            profile.steps,
+            vec![
+                StepInfo {
+                    name: "preinfusion".into(),
+                    start: 0.,
+                    end: 20.,
+                    exits: vec![
+                        StepExit {
+                            kind: ExitKind::PressureOver,
+                            value: 4.
+                        },
+                        StepExit {
+                            kind: ExitKind::Volume,
+                            value: 100.
+                        },
+                    ],
+                    exits_early: true,
+                },
+                StepInfo {
+                    name: "pour".into(),
+                    start: 20.,
+                    end: 50.,
+                    exits: vec![
+                        StepExit {
+                            kind: ExitKind::FlowOver,
+                            value: 3.
+                        },
+                        StepExit {
+                            kind: ExitKind::Weight,
+                            value: 36.
+                        },
+                    ],
+                    exits_early: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_temperature() {
+        let steps = crate::parser::steps(
+            b"{temperature 90 seconds 10 sensor water} \
+              {temperature 94 seconds 10 transition smooth} \
+              {temperature 88 seconds 5}",
+        )
+        .unwrap();
+        let profile = analyze(&steps).with_tank_temperature(Some(0.));
+        assert_eq!(
+            profile.temperature,
+            vec![
+                TemperatureSegment {
+                    position: (0., 90., 10., 90.),
+                    sensor: SensorType::Water,
+                },
+                TemperatureSegment {
+                    position: (10., 90., 20., 94.),
+                    sensor: SensorType::Coffee,
+                },
+                TemperatureSegment {
+                    position: (20., 94., 20., 88.),
+                    sensor: SensorType::Coffee,
+                },
+                TemperatureSegment {
+                    position: (20., 88., 25., 88.),
+                    sensor: SensorType::Coffee,
+                },
+            ]
+        );
+        assert_eq!(profile.tank_temperature, None);
+    }
+
+    #[test]
+    fn test_analyze_limiters() {
+        let steps = crate::parser::steps(
+            b"{flow 2 seconds 10 pump flow max_flow_or_pressure 3} \
+              {pressure 9 seconds 20 pump pressure max_flow_or_pressure 2.5 \
+              max_flow_or_pressure_range 0.2} \
+              {pressure 6 seconds 5 pump pressure max_flow_or_pressure 0}",
+        )
+        .unwrap();
+        let profile = analyze(&steps);
+        assert_eq!(
+            profile.pressure_limits,
+            vec![LimitSegment {
+                start: 0.,
+                end: 10.,
+                value: 3.,
+                range: 0.6f32 as f64,
+            }]
+        );
+        assert_eq!(
+            profile.flow_limits,
+            vec![LimitSegment {
+                start: 10.,
+                end: 30.,
+                value: 2.5,
+                range: 0.2f32 as f64,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_readout() {
+        let steps = crate::parser::steps(
+            b"{name fill flow 4 seconds 10 pump flow temperature 90} \
+              {name ramp pressure 9 seconds 10 pump pressure transition smooth temperature 94}",
+        )
+        .unwrap();
+        let profile = analyze(&steps);
+        assert_eq!(
+            profile.readout(5.),
+            Readout {
+                time: 5.,
+                pressure: None,
+                flow: Some(4.),
+                temperature: Some(90.),
+                step: Some(0),
+            }
+        );
+        let readout = profile.readout(15.);
+        assert_eq!(readout.pressure, Some(9.));
+        assert_eq!(readout.temperature, Some(92.));
+        assert_eq!(readout.step, Some(1));
+        assert_eq!(profile.readout(20.).step, Some(1));
+        assert_eq!(profile.readout(25.).step, None);
+    }
+}
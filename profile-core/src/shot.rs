@@ -0,0 +1,157 @@
+//! Shot history files, the `.shot` Tcl files the DE1 app saves with what the machine measured.
+
+use std::convert::TryFrom;
+
+use nom::character::complete::space1;
+use nom::sequence::tuple;
+use serde::{Deserialize, Serialize};
+
+use crate::encoding::decode;
+use crate::model::DecentProfile;
+use crate::parser::{key_name, profile, skip_space, ParseError};
+use crate::tcl::word;
+
+/// Measured curves of a shot, each value sampled at the time at the same index of `elapsed`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShotSeries {
+    /// Seconds since the start of the shot.
+    pub elapsed: Vec<f64>,
+    pub pressure: Vec<f64>,
+    pub flow: Vec<f64>,
+    /// Grams in the cup, empty when no scale was connected.
+    pub weight: Vec<f64>,
+    /// Temperature in the basket.
+    pub temperature: Vec<f64>,
+}
+
+impl ShotSeries {
+    pub fn duration(&self) -> f64 {
+        self.elapsed.last().copied().unwrap_or_default()
+    }
+
+    /// Samples of one of the curves as `(time, value)`.
+    pub fn points<'a>(&'a self, values: &'a [f64]) -> impl Iterator<Item = (f64, f64)> + 'a {
+        self.elapsed.iter().copied().zip(values.iter().copied())
+    }
+
+    /// Value of one of the curves at `time`, `None` before the first or after the last sample.
+    pub fn at(&self, values: &[f64], time: f64) -> Option<f64> {
+        let points = self.points(values).collect::<Vec<_>>();
+        points
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|((x1, _), (x2, _))| x1 < x2 && *x1 <= time && time <= *x2)
+            .map(|((x1, y1), (x2, y2))| y1 + (y2 - y1) * (time - x1) / (x2 - x1))
+    }
+}
+
+/// A `.shot` file with the curves and the profile the shot was pulled with.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shot {
+    pub series: ShotSeries,
+    /// Profile from the `settings` block, `None` when the file has none.
+    pub profile: Option<DecentProfile>,
+}
+
+impl Shot {
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        let decoded = decode(input);
+        let text = decoded.text.as_bytes();
+        let mut shot = Shot::default();
+
+        let mut i = skip_space(text);
+        while !i.is_empty() {
+            let (value, (key, _)) =
+                tuple((key_name, space1))(i).map_err(|_: nom::Err<nom::error::Error<_>>| {
+                    ParseError::new(text, i, None, "a shot key")
+                })?;
+            let (rest, content) = word(value)
+                .map_err(|_| ParseError::new(text, value, Some(key.into()), "a value"))?;
+            let numbers = || {
+                numbers(&content).ok_or_else(|| {
+                    ParseError::new(text, value, Some(key.into()), "a list of numbers")
+                })
+            };
+
+            match key {
+                "espresso_elapsed" => shot.series.elapsed = numbers()?,
+                "espresso_pressure" => shot.series.pressure = numbers()?,
+                "espresso_flow" => shot.series.flow = numbers()?,
+                "espresso_weight" => shot.series.weight = numbers()?,
+                "espresso_temperature_basket" => shot.series.temperature = numbers()?,
+                "settings" => {
+                    let base = Some(text.len() - value.len() + 1).filter(|_| value[0] == b'{');
+                    let commands = profile(content.as_bytes())
+                        .map_err(|e| e.relocate(text, base, value, "settings"))?;
+                    let mut profile = DecentProfile::try_from(commands)?;
+                    profile.encoding = decoded.encoding;
+                    shot.profile = Some(profile);
+                }
+                // NOTE: Machine state, app settings and the many other curves aren't drawn
+                _ => (),
+            }
+            i = skip_space(rest);
+        }
+        Ok(shot)
+    }
+}
+
+fn numbers(list: &str) -> Option<Vec<f64>> {
+    list.split_whitespace().map(|v| v.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ProfileType;
+
+    #[test]
+    fn test_parse_shot() {
+        let shot = Shot::parse(include_bytes!("../fixtures/history.shot")).unwrap();
+        let series = &shot.series;
+        assert_eq!(series.elapsed.len(), 11);
+        assert_eq!(series.pressure[7], 8.9);
+        assert_eq!(series.flow[1], 3.9);
+        assert_eq!(series.weight[10], 12.4);
+        assert_eq!(series.temperature[0], 88.1);
+        assert_eq!(series.duration(), 10.);
+
+        let profile = shot.profile.unwrap();
+        assert_eq!(profile.title.as_deref(), Some("Fill and pour"));
+        assert_eq!(profile.profile_type, Some(ProfileType::Settings2C));
+        assert_eq!(profile.steps.map(|steps| steps.len()), Some(2));
+    }
+
+    #[test]
+    fn test_parse_shot_without_settings() {
+        let shot = Shot::parse(b"espresso_elapsed {0 0.5}\nespresso_pressure {}\n").unwrap();
+        assert_eq!(shot.series.elapsed, vec![0., 0.5]);
+        assert!(shot.series.pressure.is_empty());
+        assert_eq!(shot.profile, None);
+    }
+
+    #[test]
+    fn test_parse_shot_errors() {
+        let e = Shot::parse(b"clock 1\nespresso_flow {1.0 oops}\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 15));
+        assert_eq!(e.key.as_deref(), Some("espresso_flow"));
+
+        let e = Shot::parse(b"settings {\n\tauthor Decent\n\tadvanced_shot {{pump air}}\n}\n")
+            .unwrap_err();
+        assert_eq!(e.line, 3);
+        assert_eq!(e.key.as_deref(), Some("pump"));
+    }
+
+    #[test]
+    fn test_series_at() {
+        let series = ShotSeries {
+            elapsed: vec![0., 1., 1., 3.],
+            pressure: vec![0., 2., 4., 8.],
+            ..Default::default()
+        };
+        assert_eq!(series.at(&series.pressure, 0.5), Some(1.));
+        assert_eq!(series.at(&series.pressure, 2.), Some(6.));
+        assert_eq!(series.at(&series.pressure, 3.5), None);
+        assert_eq!(series.at(&series.weight, 0.5), None);
+    }
+}
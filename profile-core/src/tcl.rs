@@ -0,0 +1,300 @@
+//! Words of Tcl lists, the syntax profile files and their `advanced_shot` steps are written in.
+
+use nom::branch::alt;
+use nom::error::{ErrorKind, ParseError};
+use nom::{Err, IResult};
+
+/// A braced, double-quoted or bare word.
+pub fn word(i: &[u8]) -> IResult<&[u8], String> {
+    alt((braced, quoted, bare))(i)
+}
+
+/// `{...}` with nested braces, kept verbatim as Tcl does no substitution inside braces.
+pub fn braced(input: &[u8]) -> IResult<&[u8], String> {
+    if input.first() != Some(&b'{') {
+        return fail(input);
+    }
+
+    let mut nest = 0;
+    let mut cursor = 1;
+    while cursor < input.len() {
+        match input[cursor] {
+            // NOTE: Escaped braces don't count, but the backslash stays in the value
+            b'\\' => cursor += 1,
+            b'{' => nest += 1,
+            b'}' if nest == 0 => {
+                let inner = &input[1..cursor];
+                return Ok((&input[(cursor + 1)..], lossy(inner)));
+            }
+            b'}' => nest -= 1,
+            _ => (),
+        }
+        cursor += 1;
+    }
+    fail(&input[1..])
+}
+
+/// `"..."` with backslash substitution.
+pub fn quoted(input: &[u8]) -> IResult<&[u8], String> {
+    if input.first() != Some(&b'"') {
+        return fail(input);
+    }
+
+    let mut cursor = 1;
+    while cursor < input.len() {
+        match input[cursor] {
+            b'\\' => cursor += 1,
+            b'"' => {
+                let inner = &input[1..cursor];
+                return Ok((&input[(cursor + 1)..], substitute(inner)));
+            }
+            _ => (),
+        }
+        cursor += 1;
+    }
+    fail(&input[1..])
+}
+
+/// Runs until white space or the `}` closing the enclosing list, with backslash substitution.
+pub fn bare(input: &[u8]) -> IResult<&[u8], String> {
+    // NOTE: An opening brace or quote without its closing one is an error, not a bare word
+    if matches!(input.first(), Some(b'{' | b'"')) {
+        return fail(input);
+    }
+
+    let mut cursor = 0;
+    while cursor < input.len() {
+        match input[cursor] {
+            b'\\' => cursor += 1,
+            c if c.is_ascii_whitespace() || c == b'}' => break,
+            _ => (),
+        }
+        cursor += 1;
+    }
+    // NOTE: A trailing lone backslash moves the cursor past the end
+    let cursor = cursor.min(input.len());
+    if cursor == 0 {
+        return fail(input);
+    }
+    Ok((&input[cursor..], substitute(&input[..cursor])))
+}
+
+fn fail<T>(input: &[u8]) -> IResult<&[u8], T> {
+    Err(Err::Error(nom::error::Error::from_error_kind(
+        input,
+        ErrorKind::Fail,
+    )))
+}
+
+fn lossy(v: &[u8]) -> String {
+    String::from_utf8_lossy(v).into_owned()
+}
+
+/// Replaces backslash sequences the way the Tcl parser does.
+fn substitute(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] != b'\\' || i + 1 == raw.len() {
+            out.push(raw[i]);
+            i += 1;
+            continue;
+        }
+
+        let c = raw[i + 1];
+        i += 2;
+        match c {
+            b'a' => out.push(0x07),
+            b'b' => out.push(0x08),
+            b'f' => out.push(0x0c),
+            b'n' => out.push(b'\n'),
+            b'r' => out.push(b'\r'),
+            b't' => out.push(b'\t'),
+            b'v' => out.push(0x0b),
+            b'\n' => {
+                while i < raw.len() && matches!(raw[i], b' ' | b'\t') {
+                    i += 1;
+                }
+                out.push(b' ');
+            }
+            b'x' | b'u' | b'U' => {
+                let max = match c {
+                    b'x' => 2,
+                    b'u' => 4,
+                    _ => 8,
+                };
+                let (value, len) = digits(&raw[i..], 16, max);
+                match char::from_u32(value).filter(|_| len > 0) {
+                    Some(ch) => out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+                    // NOTE: Without digits the letter itself is the value, e.g. `\x` is `x`
+                    None => out.push(c),
+                }
+                i += len;
+            }
+            b'0'..=b'7' => {
+                let (value, len) = digits(&raw[(i - 1)..], 8, 3);
+                let ch = char::from_u32(value & 0xff).expect("octal escapes are below 0x100");
+                out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                i += len - 1;
+            }
+            // NOTE: Covers `\{`, `\}`, `\"`, `\\`, `\ ` and also the first byte of a UTF-8 char
+            _ => out.push(c),
+        }
+    }
+    lossy(&out)
+}
+
+/// Value of up to `max` leading digits in `radix` and how many there were.
+fn digits(raw: &[u8], radix: u32, max: usize) -> (u32, usize) {
+    raw.iter()
+        .take(max)
+        .map_while(|c| (*c as char).to_digit(radix))
+        .fold((0, 0), |(value, len), d| (value * radix + d, len + 1))
+}
+
+/// Quotes `v` so that `word` reads it back unchanged, in the way the DE1 app writes it.
+pub fn quote(v: &str) -> String {
+    let special =
+        |c: char| c.is_whitespace() || matches!(c, '{' | '}' | '"' | '\\' | ';' | '[' | ']' | '$');
+    if v.is_empty() {
+        "{}".into()
+    } else if !v.chars().any(special) {
+        v.into()
+    } else if braceable(v) {
+        format!("{{{}}}", v)
+    } else {
+        escape(v)
+    }
+}
+
+/// Braces only round trip when they are balanced and the last one isn't escaped.
+fn braceable(v: &str) -> bool {
+    let bytes = v.as_bytes();
+    let mut nest = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if i + 1 == bytes.len() => return false,
+            b'\\' => i += 1,
+            b'{' => nest += 1,
+            b'}' if nest == 0 => return false,
+            b'}' => nest -= 1,
+            _ => (),
+        }
+        i += 1;
+    }
+    nest == 0
+}
+
+fn escape(v: &str) -> String {
+    let mut out = String::with_capacity(v.len() * 2);
+    for c in v.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '{' | '}' | '"' | '\\' | ';' | '[' | ']' | '$' | ' ' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_braced() {
+        assert_eq!(braced(b"{} rest"), Ok((&b" rest"[..], "".into())));
+        assert_eq!(
+            braced(b"{volume 150.0} rest"),
+            Ok((&b" rest"[..], "volume 150.0".into()))
+        );
+        assert_eq!(
+            braced(b"{{exit_if 0} {volume 150.0 name {Hoge}}}"),
+            Ok((&b""[..], "{exit_if 0} {volume 150.0 name {Hoge}}".into()))
+        );
+        assert_eq!(
+            braced(br"{a \} b} rest"),
+            Ok((&b" rest"[..], r"a \} b".into()))
+        );
+        assert_eq!(
+            braced(b"{{exit_if 0} {volume 150.0} unmatched"),
+            Err(Err::Error(Error::from_error_kind(
+                &b"{exit_if 0} {volume 150.0} unmatched"[..],
+                ErrorKind::Fail
+            )))
+        );
+    }
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(
+            quoted(br#""Pressure Up" rest"#),
+            Ok((&b" rest"[..], "Pressure Up".into()))
+        );
+        assert_eq!(
+            quoted(br#""say \"hi\"\nnow""#),
+            Ok((&b""[..], "say \"hi\"\nnow".into()))
+        );
+        assert!(quoted(br#""unterminated"#).is_err());
+    }
+
+    #[test]
+    fn test_bare() {
+        assert_eq!(bare(b"Fill ;"), Ok((&b" ;"[..], "Fill".into())));
+        assert_eq!(bare(b"Fill}"), Ok((&b"}"[..], "Fill".into())));
+        assert_eq!(bare(br"a\ b\}c d"), Ok((&b" d"[..], "a b}c".into())));
+        assert!(bare(b" Fill").is_err());
+        assert!(bare(b"{unterminated").is_err());
+    }
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(substitute(br"tab\there"), "tab\there");
+        assert_eq!(substitute(b"line\\\n    next"), "line next");
+        assert_eq!(substitute(br"\u00e9t\u00E9"), "été");
+        assert_eq!(substitute(br"\x41\101\x"), "AAx");
+        assert_eq!(substitute("\\caf\u{e9}".as_bytes()), "café");
+        assert_eq!(substitute(br"trailing\"), r"trailing\");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("Fill"), "Fill");
+        assert_eq!(quote("Pressure Up"), "{Pressure Up}");
+        assert_eq!(quote(""), "{}");
+        assert_eq!(quote("{exit_if 0}"), "{{exit_if 0}}");
+        assert_eq!(quote("unbalanced } brace"), r"unbalanced\ \}\ brace");
+        assert_eq!(quote(r"ends with \"), r"ends\ with\ \\");
+    }
+
+    #[test]
+    fn test_quote_round_trip() {
+        let values = [
+            "Fill",
+            "Pressure Up",
+            "first\nsecond",
+            "{a} {b {c}}",
+            "a } b { c",
+            r"back\slash",
+            "tab\tand \"quotes\" [cmd] $var;",
+            "日本語 ☕",
+            "",
+        ];
+        for v in values.iter() {
+            assert_eq!(
+                word(quote(v).as_bytes()),
+                Ok((&b""[..], v.to_string())),
+                "{}",
+                v
+            );
+        }
+    }
+}
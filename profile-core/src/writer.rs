@@ -0,0 +1,163 @@
+use crate::parser::{Command, Prop, Step};
+use crate::tcl::quote;
+
+pub fn write_profile(commands: &[Command]) -> String {
+    commands
+        .iter()
+        .map(|cmd| format!("{} {}\n", cmd.key(), command_value(cmd)))
+        .collect()
+}
+
+pub fn write_steps(steps: &[Step]) -> String {
+    steps.iter().map(write_step).collect::<Vec<_>>().join(" ")
+}
+
+fn write_step(step: &Step) -> String {
+    let props = step
+        .0
+        .iter()
+        .map(|prop| format!("{} {}", prop.key(), prop_value(prop)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", props.join(" "))
+}
+
+fn command_value(cmd: &Command) -> String {
+    match cmd {
+        Command::AdvancedShot(v)
+        | Command::Author(v)
+        | Command::ProfileLanguage(v)
+        | Command::ProfileNotes(v)
+        | Command::ProfileTitle(v)
+        | Command::BeanBrand(v)
+        | Command::BeanType(v)
+        | Command::GrinderModel(v)
+        | Command::GrinderSetting(v) => quote(v),
+        Command::EspressoDeclineTime(v)
+        | Command::EspressoHoldTime(v)
+        | Command::EspressoPressure(v)
+        | Command::EspressoTemperature(v)
+        | Command::EspressoTemperature0(v)
+        | Command::EspressoTemperature1(v)
+        | Command::EspressoTemperature2(v)
+        | Command::EspressoTemperature3(v)
+        | Command::FinalDesiredShotVolume(v)
+        | Command::FinalDesiredShotVolumeAdvanced(v)
+        | Command::FinalDesiredShotVolumeAdvancedCountStart(v)
+        | Command::FinalDesiredShotWeight(v)
+        | Command::FinalDesiredShotWeightAdvanced(v)
+        | Command::FlowProfileDecline(v)
+        | Command::FlowProfileDeclineTime(v)
+        | Command::FlowProfileHold(v)
+        | Command::FlowProfileHoldTime(v)
+        | Command::FlowProfileMinimumPressure(v)
+        | Command::FlowProfilePreinfusion(v)
+        | Command::FlowProfilePreinfusionTime(v)
+        | Command::MaximumFlow(v)
+        | Command::MaximumFlowRange(v)
+        | Command::MaximumFlowRangeAdvanced(v)
+        | Command::MaximumFlowRangeDefault(v)
+        | Command::MaximumPressure(v)
+        | Command::MaximumPressureRange(v)
+        | Command::MaximumPressureRangeAdvanced(v)
+        | Command::MaximumPressureRangeDefault(v)
+        | Command::PreinfusionFlowRate(v)
+        | Command::PreinfusionStopPressure(v)
+        | Command::PreinfusionTime(v)
+        | Command::PressureEnd(v)
+        | Command::TankDesiredWaterTemperature(v)
+        | Command::WaterTemperature(v)
+        | Command::GrinderDoseWeight(v) => v.to_string(),
+        Command::EspressoTemperatureStepsEnabled(v)
+        | Command::PreinfusionGuarantee(v)
+        | Command::ProfileHide(v) => boolean(*v).into(),
+        Command::BeverageType(v) => v.to_string(),
+        Command::SettingsProfileType(v) => v.to_string(),
+        Command::Unknown((_, v)) => v.clone(),
+    }
+}
+
+/// Value of a step property as it is written in the Tcl file.
+pub fn prop_value(prop: &Prop) -> String {
+    match prop {
+        Prop::ExitIf(v) => boolean(*v).into(),
+        Prop::Flow(v)
+        | Prop::Volume(v)
+        | Prop::MaxFlowOrPressureRange(v)
+        | Prop::ExitFlowUnder(v)
+        | Prop::Temperature(v)
+        | Prop::Pressure(v)
+        | Prop::ExitFlowOver(v)
+        | Prop::ExitPressureOver(v)
+        | Prop::MaxFlowOrPressure(v)
+        | Prop::ExitPressureUnder(v)
+        | Prop::Seconds(v)
+        | Prop::Weight(v) => v.to_string(),
+        Prop::Transition(v) => v.to_string(),
+        Prop::Sensor(v) => v.to_string(),
+        Prop::Pump(v) => v.to_string(),
+        Prop::ExitType(v) => v.to_string(),
+        Prop::Name(v) => quote(v),
+        Prop::Unknown((_, v)) => v.clone(),
+    }
+}
+
+fn boolean(v: bool) -> &'static str {
+    if v {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{profile, steps, PumpType, TransitionType};
+
+    #[test]
+    fn test_write_steps() {
+        let data = vec![
+            Step(vec![
+                Prop::Name("Pressure Up".into()),
+                Prop::Pump(PumpType::Pressure),
+                Prop::Transition(TransitionType::Smooth),
+                Prop::Pressure(9.0.into()),
+                Prop::Seconds(4.5.into()),
+            ]),
+            Step(vec![Prop::ExitIf(false), Prop::Flow(2.0.into())]),
+        ];
+        let text = write_steps(&data);
+        assert_eq!(
+            text,
+            "{name {Pressure Up} pump pressure transition smooth pressure 9 seconds 4.5} {exit_if 0 flow 2}"
+        );
+        assert_eq!(steps(text.as_bytes()), Ok(data));
+    }
+
+    #[test]
+    fn test_write_unknown_verbatim() {
+        let payload = b"profile_video_help {https://example.com/a b}\nadvanced_shot {{flow 2 popup {Hi there}}}\n";
+        let commands = profile(payload).unwrap();
+        assert_eq!(write_profile(&commands).as_bytes(), &payload[..]);
+    }
+
+    #[test]
+    fn test_write_profile_round_trip() {
+        let payload = include_str!("../fixtures/profile.tcl");
+        let commands = profile(payload.as_bytes()).unwrap();
+        let text = write_profile(&commands);
+        assert_eq!(profile(text.as_bytes()), Ok(commands));
+    }
+
+    #[test]
+    fn test_write_number_lexemes() {
+        let payload = b"advanced_shot {{pressure -3.885780586188048e-16 seconds 20.00}}
+espresso_pressure 8.40
+tank_desired_water_temperature 0
+final_desired_shot_weight 1e2
+my_counter 70000
+";
+        let commands = profile(payload).unwrap();
+        assert_eq!(write_profile(&commands).as_bytes(), &payload[..]);
+    }
+}
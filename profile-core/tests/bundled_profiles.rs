@@ -0,0 +1,104 @@
+use std::convert::TryFrom;
+
+use profile_core::json;
+use profile_core::model::{DecentProfile, StepSpec};
+use profile_core::parser::{profile, Step};
+use profile_core::profile::analyze;
+use profile_core::writer::write_profile;
+
+/// Name and content of every profile bundled with the web app.
+fn bundled_profiles() -> Vec<(String, Vec<u8>)> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../web/profiles");
+    let mut profiles = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, std::fs::read(&path).unwrap())
+        })
+        .collect::<Vec<_>>();
+    profiles.sort();
+    assert!(!profiles.is_empty(), "no profiles in {}", dir);
+    profiles
+}
+
+/// Every bundled profile parsed, failing on the first one that doesn't parse.
+fn parsed_profiles() -> Vec<(String, DecentProfile)> {
+    bundled_profiles()
+        .into_iter()
+        .map(|(name, content)| {
+            let profile =
+                DecentProfile::parse(&content).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            (name, profile)
+        })
+        .collect()
+}
+
+fn specs(steps: &[Step]) -> Vec<StepSpec> {
+    steps.iter().map(StepSpec::from).collect()
+}
+
+#[test]
+fn test_parse_bundled_profiles() {
+    assert_eq!(parsed_profiles().len(), bundled_profiles().len());
+}
+
+#[test]
+fn test_analyze_bundled_profiles() {
+    for (name, profile) in parsed_profiles() {
+        // NOTE: Profile types without steps, like "settings_1", have nothing to analyze
+        let steps = match profile.shot_steps() {
+            Some(steps) => steps,
+            None => continue,
+        };
+        let analyzed = analyze(&steps);
+        let positions = analyzed
+            .temperature
+            .iter()
+            .map(|segment| &segment.position)
+            .chain(analyzed.pressure.iter())
+            .chain(analyzed.flow.iter());
+        for (x1, y1, x2, y2) in positions {
+            assert!([x1, y1, x2, y2].iter().all(|v| v.is_finite()), "{}", name);
+            assert!(x1 <= x2 && *x2 <= analyzed.elapsed_time, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_commands_round_trip_bundled_profiles() {
+    for (name, profile) in parsed_profiles() {
+        let commands = profile.to_commands();
+        assert_eq!(DecentProfile::try_from(commands), Ok(profile), "{}", name);
+    }
+}
+
+#[test]
+fn test_write_round_trip_bundled_profiles() {
+    for (name, content) in bundled_profiles() {
+        let commands = profile(&content).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        let text = write_profile(&commands);
+        let read = profile(text.as_bytes()).unwrap();
+        assert_eq!(read, commands, "{}", name);
+        // NOTE: Numbers compare by value, so the text shows whether lexemes survived
+        assert_eq!(write_profile(&read), text, "{}", name);
+    }
+}
+
+#[test]
+fn test_json_round_trip_bundled_profiles() {
+    for (name, profile) in parsed_profiles() {
+        let read = json::profile(&json::write_profile(&profile)).unwrap();
+        assert_eq!(
+            read.shot_steps().map(|steps| specs(&steps)),
+            profile
+                .shot_steps()
+                .map(|steps| specs(&steps))
+                .or(Some(vec![])),
+            "{}",
+            name
+        );
+        assert_eq!(read.title, profile.title, "{}", name);
+        assert_eq!(read.notes, profile.notes, "{}", name);
+    }
+}
@@ -1,112 +1,287 @@
-use crate::components::{axis::Direction, Axis};
-use crate::lib::profile::AnalyzedProfile;
-use crate::lib::scale;
-use crate::prelude::*;
+use web_sys::Element;
+use yew::{Callback, MouseEvent, TargetCast};
 
-static OUTER: (f64, f64) = (1024., 480.);
-static INNER: (f64, f64, f64, f64) = (30., 20., 1004., 450.);
+use super::Axis;
+use crate::lib::chart::{polyline_points, Chart, Shape, DEFAULT_SIZE};
+use crate::lib::profile::{AnalyzedProfile, Readout};
+use crate::prelude::*;
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub data: AnalyzedProfile,
+    /// Index of the clicked step, `None` when clicked again to deselect.
+    #[prop_or_default]
+    pub onselect: Callback<Option<usize>>,
+}
+
+pub struct Graph {
+    chart: Chart,
+    /// Time under the mouse pointer.
+    hover: Option<f64>,
+    selected: Option<usize>,
 }
 
-#[derive(PartialEq)]
-pub struct Graph;
+/// Horizontal positions are in chart coordinates.
+pub enum Msg {
+    /// `None` once the pointer leaves.
+    Hover(Option<f64>),
+    Select(f64),
+}
 
 impl Component for Graph {
-    type Message = ();
+    type Message = Msg;
     type Properties = Props;
 
-    fn create(_: &Context<Self>) -> Self {
-        Self
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            chart: Chart::new(&ctx.props().data, DEFAULT_SIZE),
+            hover: None,
+            selected: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Hover(x) => {
+                let hover = x.and_then(|x| self.chart.time_at(x));
+                let changed = hover != self.hover;
+                self.hover = hover;
+                changed
+            }
+            Msg::Select(x) => {
+                let step = self
+                    .chart
+                    .time_at(x)
+                    .and_then(|time| ctx.props().data.readout(time).step);
+                self.selected = if step == self.selected { None } else { step };
+                ctx.props().onselect.emit(self.selected);
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.chart = Chart::new(&ctx.props().data, DEFAULT_SIZE);
+        self.hover = None;
+        self.selected = None;
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let chart = &self.chart;
+        let (left, top, right, bottom) = chart.area;
+        let x_of = move |e: MouseEvent| {
+            let rect = e
+                .target_unchecked_into::<Element>()
+                .get_bounding_client_rect();
+            // NOTE: Goes through the rendered size in case CSS scales the SVG down
+            left + (e.client_x() as f64 - rect.left()) * (right - left) / rect.width()
+        };
+        let onmousemove = ctx.link().callback(move |e| Msg::Hover(Some(x_of(e))));
+        let onmouseleave = ctx.link().callback(|_| Msg::Hover(None));
+        let onclick = ctx.link().callback(move |e| Msg::Select(x_of(e)));
+        let highlight = self.selected.map(|i| {
+            let step = &ctx.props().data.steps[i];
+            Shape::Rect {
+                x: chart.x_at(step.start),
+                y: top,
+                width: chart.x_at(step.end) - chart.x_at(step.start),
+                height: bottom - top,
+                fill: "gold",
+                opacity: 0.2,
+            }
+        });
+        let tooltip = self
+            .hover
+            .map(|time| tooltip(chart, &ctx.props().data, time))
+            .unwrap_or_default();
+
         html! {
-            <svg width={ format!("{}px", OUTER.0) } height={ format!("{}px", OUTER.1) } viewBox={ format!("0 0 {} {}", OUTER.0, OUTER.1) }>
-                {self.view_axis(ctx)}
-                <g>
-                    {self.view_graph_temperature(ctx)}
-                    {self.view_graph_pressure(ctx)}
-                    {self.view_graph_flow(ctx)}
-                </g>
+            <svg width={ format!("{}px", chart.width) } height={ format!("{}px", chart.height) } viewBox={ format!("0 0 {} {}", chart.width, chart.height) }>
+                { for chart.shapes[..chart.axes.start].iter().map(view_shape) }
+                <Axis shapes={chart.shapes[chart.axes.clone()].to_vec()} />
+                { for chart.shapes[chart.axes.end..].iter().map(view_shape) }
+                { for highlight.iter().map(view_shape) }
+                { for tooltip.iter().map(view_shape) }
+                <rect
+                    x={left.to_string()}
+                    y={top.to_string()}
+                    width={(right - left).to_string()}
+                    height={(bottom - top).to_string()}
+                    fill="none"
+                    pointer-events="all"
+                    {onmousemove}
+                    {onmouseleave}
+                    {onclick}
+                />
             </svg>
         }
     }
 }
 
-impl Graph {
-    fn view_axis(&self, ctx: &Context<Self>) -> Html {
-        html! {
-            <g>
-                <g transform={ format!("translate({},{})", INNER.0, INNER.3) }>
-                    <Axis domain={(0., ctx.props().data.elapsed_time)} range={(0., INNER.2 - INNER.0)} dir={Direction::Horizontal} min_unit={10.0} />
-                </g>
-                <g transform={ format!("translate({},{})", INNER.0, INNER.3) }>
-                    <Axis domain={(0., 12.)} range={(0., INNER.1 - INNER.3)} dir={Direction::Vertical} min_unit={1.0} />
-                </g>
-            </g>
-        }
-    }
+/// Crosshair at `time` with a box listing the targets there.
+fn tooltip(chart: &Chart, data: &AnalyzedProfile, time: f64) -> Vec<Shape> {
+    const WIDTH: f64 = 170.;
+    const LINE_HEIGHT: f64 = 16.;
 
-    fn view_graph_temperature(&self, ctx: &Context<Self>) -> Html {
-        let x = scale((0., ctx.props().data.elapsed_time), (INNER.0, INNER.2));
-        let y = scale((20., 100.), (INNER.3, INNER.1));
-        html! {
-            ctx.props().data.temperature.iter().map(|(x1, y1, x2, y2)| {
-                html! {
-                    <line
-                        x1={x(*x1).to_string()}
-                        y1={y(*y1).to_string()}
-                        x2={x(*x2).to_string()}
-                        y2={y(*y2).to_string()}
-                        stroke="darkred"
-                        stroke-width="1.5px"
-                        stroke-linecap="round"
-                    />
-                }
-            }).collect::<Html>()
+    let Readout {
+        pressure,
+        flow,
+        temperature,
+        step,
+        ..
+    } = data.readout(time);
+    let value = |v: Option<f64>, unit: &str| match v {
+        Some(v) => format!("{:.1} {}", v, unit),
+        None => "-".into(),
+    };
+    let step = step.map(|i| match data.steps[i].name.as_str() {
+        "" => format!("Step {}", i + 1),
+        name => name.to_string(),
+    });
+    let mut lines = vec![(format!("{:.1} s", time), "black")];
+    if let Some(step) = step {
+        lines.push((step, "black"));
+    }
+    lines.push((format!("Pressure {}", value(pressure, "bar")), "darkgreen"));
+    lines.push((format!("Flow {}", value(flow, "ml/s")), "darkblue"));
+    lines.push((
+        format!("Temperature {}", value(temperature, "°C")),
+        "darkred",
+    ));
+    if let Some(shot) = &data.shot {
+        // NOTE: Weight isn't drawn, so this is the only place it shows up
+        let measured = [
+            (&shot.pressure, "bar", "limegreen"),
+            (&shot.flow, "ml/s", "dodgerblue"),
+            (&shot.weight, "g", "saddlebrown"),
+            (&shot.temperature, "°C", "tomato"),
+        ];
+        let values = measured
+            .iter()
+            .filter_map(|(values, unit, fill)| {
+                shot.at(values, time)
+                    .map(|v| (format!("{:.1} {}", v, unit), *fill))
+            })
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            lines.push(("Shot".into(), "black"));
+            lines.extend(values);
         }
     }
 
-    fn view_graph_pressure(&self, ctx: &Context<Self>) -> Html {
-        let x = scale((0., ctx.props().data.elapsed_time), (INNER.0, INNER.2));
-        let y = scale((0., 12.), (INNER.3, INNER.1));
-        html! {
-            ctx.props().data.pressure.iter().map(|(x1, y1, x2, y2)| {
-                html! {
-                    <line
-                        x1={x(*x1).to_string()}
-                        y1={y(*y1).to_string()}
-                        x2={x(*x2).to_string()}
-                        y2={y(*y2).to_string()}
-                        stroke="darkgreen"
-                        stroke-width="1.5px"
-                        stroke-linecap="round"
-                    />
-                }
-            }).collect::<Html>()
-        }
+    let (_, top, right, bottom) = chart.area;
+    let x = chart.x_at(time);
+    // NOTE: Flips to the left of the crosshair near the right edge to stay inside the chart
+    let box_x = if x + 10. + WIDTH > right {
+        x - 10. - WIDTH
+    } else {
+        x + 10.
+    };
+    let box_y = top + 8.;
+
+    let mut shapes = vec![
+        Shape::Line {
+            x1: x,
+            y1: top,
+            x2: x,
+            y2: bottom,
+            stroke: "dimgray",
+            stroke_width: 1.,
+            dash: Some("3 3"),
+        },
+        Shape::Rect {
+            x: box_x,
+            y: box_y,
+            width: WIDTH,
+            height: LINE_HEIGHT * lines.len() as f64 + 8.,
+            fill: "white",
+            opacity: 0.9,
+        },
+    ];
+    for (i, (text, fill)) in lines.into_iter().enumerate() {
+        shapes.push(Shape::Text {
+            x: box_x + 8.,
+            y: box_y + LINE_HEIGHT * (i + 1) as f64,
+            text,
+            anchor: "start",
+            fill,
+            font_size: 12.,
+        });
     }
+    shapes
+}
 
-    fn view_graph_flow(&self, ctx: &Context<Self>) -> Html {
-        let x = scale((0., ctx.props().data.elapsed_time), (INNER.0, INNER.2));
-        let y = scale((0., 12.), (INNER.3, INNER.1));
-        html! {
-            ctx.props().data.flow.iter().map(|(x1, y1, x2, y2)| {
-                html! {
-                    <line
-                        x1={x(*x1).to_string()}
-                        y1={y(*y1).to_string()}
-                        x2={x(*x2).to_string()}
-                        y2={y(*y2).to_string()}
-                        stroke="darkblue"
-                        stroke-width="1.5px"
-                        stroke-linecap="round"
-                    />
-                }
-            }).collect::<Html>()
-        }
+pub(super) fn view_shape(shape: &Shape) -> Html {
+    match shape {
+        Shape::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            stroke,
+            stroke_width,
+            dash,
+        } => html! {
+            <line
+                x1={x1.to_string()}
+                y1={y1.to_string()}
+                x2={x2.to_string()}
+                y2={y2.to_string()}
+                stroke={*stroke}
+                stroke-width={format!("{}px", stroke_width)}
+                stroke-linecap="round"
+                stroke-dasharray={*dash}
+            />
+        },
+        Shape::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            opacity,
+        } => html! {
+            <rect
+                x={x.to_string()}
+                y={y.to_string()}
+                width={width.to_string()}
+                height={height.to_string()}
+                fill={*fill}
+                fill-opacity={opacity.to_string()}
+            />
+        },
+        Shape::Text {
+            x,
+            y,
+            text,
+            anchor,
+            fill,
+            font_size,
+        } => html! {
+            <text
+                x={x.to_string()}
+                y={y.to_string()}
+                text-anchor={*anchor}
+                fill={*fill}
+                font-size={format!("{}px", font_size)}
+                font-family="sans-serif"
+            >
+                { text.as_str() }
+            </text>
+        },
+        Shape::Polyline {
+            points,
+            stroke,
+            stroke_width,
+        } => html! {
+            <polyline
+                points={polyline_points(points)}
+                fill="none"
+                stroke={*stroke}
+                stroke-width={format!("{}px", stroke_width)}
+                stroke-linejoin="round"
+            />
+        },
     }
 }
@@ -1,4 +1,4 @@
-pub mod axis;
+mod axis;
 mod content;
 mod description;
 pub mod graph;
@@ -8,6 +8,7 @@ mod logo;
 mod page;
 mod preset_item;
 mod preset_list;
+mod step_panel;
 mod title;
 
 pub use axis::Axis;
@@ -20,4 +21,5 @@ pub use logo::Logo;
 pub use page::Page;
 pub use preset_item::PresetItem;
 pub use preset_list::PresetList;
+pub use step_panel::StepPanel;
 pub use title::Title;
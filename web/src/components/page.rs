@@ -43,6 +43,7 @@ impl Component for Page {
           "#)}>
                 <Logo to={Route::Home} />
                 <Link to={Route::PresetIndex}>{ "Presets" }</Link>
+                <Link to={Route::Viewer}>{ "Open" }</Link>
                 <Link to={Route::About}>{ "About" }</Link>
               </div>
             </header>
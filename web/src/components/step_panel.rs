@@ -0,0 +1,42 @@
+use crate::lib::parser::Step;
+use crate::lib::writer::prop_value;
+use crate::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub index: usize,
+    pub step: Step,
+}
+
+/// Raw properties of a step as they appear in the profile file.
+#[function_component(StepPanel)]
+pub fn step_panel(props: &Props) -> Html {
+    html! {
+        <aside class={css!(r#"
+            min-width: 200px;
+            padding: 8px 12px;
+            border-left: 3px solid gold;
+            font-size: 14px;
+        "#)}>
+            <h3 class={css!(r#"
+                margin: 0 0 8px;
+                font-size: 16px;
+            "#)}>
+                { format!("Step {}", props.index + 1) }
+            </h3>
+            <table>
+                { for props.step.0.iter().map(|prop| html! {
+                    <tr>
+                        <th class={css!(r#"
+                            padding-right: 12px;
+                            text-align: left;
+                            font-weight: normal;
+                            color: dimgray;
+                        "#)}>{ prop.key() }</th>
+                        <td><code>{ prop_value(prop) }</code></td>
+                    </tr>
+                }) }
+            </table>
+        </aside>
+    }
+}
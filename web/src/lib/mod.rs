@@ -1,5 +1,3 @@
-pub mod parser;
-pub mod profile;
-mod scale;
+pub mod presets;
 
-pub use scale::scale;
+pub use profile_core::{chart, model, parser, profile, shot, writer};
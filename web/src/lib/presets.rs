@@ -0,0 +1,131 @@
+use include_dir::{include_dir, Dir};
+use once_cell::sync::Lazy;
+
+use crate::lib::model::DecentProfile;
+use crate::lib::parser::{ParseError, Step};
+use crate::lib::shot::{Shot, ShotSeries};
+
+static PROFILES_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/profiles");
+
+pub static PROFILES: Lazy<Vec<Preset>> = Lazy::new(|| {
+    let mut items = PROFILES_DIR
+        .files()
+        .filter_map(|file| {
+            let name = file.path().file_name().unwrap().to_str().unwrap();
+            Preset::parse(name, file.contents())
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.title.cmp(&b.title));
+    items
+});
+
+#[derive(Clone, Default)]
+pub struct Preset {
+    pub name: String,
+    pub title: String,
+    pub notes: String,
+    pub steps: Vec<Step>,
+    pub tank_temperature: Option<f32>,
+    /// `lang` of the title and notes, so browsers pick fonts for the right script.
+    pub language: Option<String>,
+    /// Legacy encoding the file was decoded from.
+    pub encoding: Option<&'static str>,
+    /// Measured curves when opened from a `.shot` file.
+    pub shot: Option<Box<ShotSeries>>,
+    pub error: Option<ParseError>,
+}
+
+impl Preset {
+    /// Returns `None` for profile types the viewer can't draw.
+    pub fn parse(name: &str, content: &[u8]) -> Option<Preset> {
+        let mut preset = Preset {
+            name: name.to_string(),
+            ..Default::default()
+        };
+
+        let profile = match DecentProfile::parse(content) {
+            Ok(profile) => profile,
+            Err(e) => {
+                // NOTE: Keep broken files listed so the viewer can report where they fail
+                preset.title = preset.name.clone();
+                preset.error = Some(e);
+                return Some(preset);
+            }
+        };
+        preset.with_profile(profile)
+    }
+
+    /// Profile a shot history file was pulled with, along with its measured curves.
+    pub fn parse_shot(name: &str, content: &[u8]) -> Option<Preset> {
+        let mut preset = Preset {
+            name: name.to_string(),
+            title: name.to_string(),
+            ..Default::default()
+        };
+
+        let shot = match Shot::parse(content) {
+            Ok(shot) => shot,
+            Err(e) => {
+                preset.error = Some(e);
+                return Some(preset);
+            }
+        };
+        preset.shot = Some(Box::new(shot.series));
+        match shot.profile {
+            Some(profile) => preset.with_profile(profile),
+            // NOTE: The curves are still worth a look without targets
+            None => Some(preset),
+        }
+    }
+
+    /// Returns `None` for profile types the viewer can't draw.
+    fn with_profile(mut self, profile: DecentProfile) -> Option<Preset> {
+        self.steps = profile.shot_steps()?;
+        self.tank_temperature = profile.temperature.tank;
+        self.language = profile.language_tag();
+        self.encoding = profile.encoding;
+        self.title = profile.title.unwrap_or_else(|| self.name.clone());
+        self.notes = profile.notes.unwrap_or_default();
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_include_simple_profiles() {
+        let titles = PROFILES
+            .iter()
+            .map(|preset| preset.title.as_str())
+            .collect::<Vec<_>>();
+        assert!(titles.contains(&"Classic Italian espresso"));
+        assert!(titles.contains(&"Flow profile for straight espresso"));
+    }
+
+    #[test]
+    fn test_preset_shot() {
+        let content = b"espresso_elapsed {0 1 2}\nespresso_pressure {0 4 9}\nsettings {\n\tprofile_title {Fill and pour}\n\tsettings_profile_type settings_2c\n\tadvanced_shot {{pump flow flow 4 seconds 10}}\n}\n";
+        let preset = Preset::parse_shot("history.shot", content).unwrap();
+        assert_eq!(preset.title, "Fill and pour");
+        assert_eq!(preset.steps.len(), 1);
+        assert_eq!(preset.shot.unwrap().pressure, vec![0., 4., 9.]);
+
+        let preset = Preset::parse_shot("broken.shot", b"espresso_flow {1 x}").unwrap();
+        assert_eq!(preset.title, "broken.shot");
+        assert!(preset.error.is_some());
+    }
+
+    #[test]
+    fn test_preset_legacy_encoding() {
+        let preset = Preset::parse(
+            "latin1.tcl",
+            b"profile_title {Caf\xe9}\nprofile_language de\nsettings_profile_type settings_2c\n",
+        )
+        .unwrap();
+        assert_eq!(preset.title, "Café");
+        assert_eq!(preset.language.as_deref(), Some("de"));
+        assert_eq!(preset.encoding, Some("windows-1252"));
+    }
+}
@@ -1,3 +1,5 @@
 mod not_found;
+mod parse_error;
 
 pub use not_found::NotFoundPage;
+pub use parse_error::ParseErrorPage;
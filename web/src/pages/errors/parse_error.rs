@@ -0,0 +1,33 @@
+use crate::components::{Content, Heading};
+use crate::lib::parser::ParseError;
+use crate::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct Props {
+    pub name: String,
+    pub error: ParseError,
+}
+
+pub struct ParseErrorPage;
+
+impl Component for ParseErrorPage {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let error = &ctx.props().error;
+        html! {
+            <Page title="Parse Error">
+                <Heading>{ ctx.props().name.as_str() }</Heading>
+                <Content>
+                    <div>{ format!("Failed to parse at line {}, column {}", error.line, error.column) }</div>
+                    <pre>{ error.to_string() }</pre>
+                </Content>
+            </Page>
+        }
+    }
+}
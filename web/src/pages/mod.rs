@@ -4,6 +4,6 @@ mod presets;
 pub(crate) mod viewer;
 
 pub use about::AboutPage;
-pub use errors::NotFoundPage;
+pub use errors::{NotFoundPage, ParseErrorPage};
 pub use presets::PresetListPage;
 pub use viewer::ViewerPage;
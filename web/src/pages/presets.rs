@@ -1,5 +1,5 @@
 use crate::components::{Content, Heading, PresetItem, PresetList};
-use crate::lib::profile::PROFILES;
+use crate::lib::presets::PROFILES;
 use crate::prelude::*;
 
 pub struct PresetListPage;
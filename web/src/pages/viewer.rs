@@ -1,16 +1,39 @@
+use gloo_net::http::Request;
 use serde::{Deserialize, Serialize};
+use web_sys::{File, HtmlInputElement};
+use yew::{Event, TargetCast};
 use yew_router::{history::Location, prelude::RouterScopeExt};
 
-use crate::components::{Content, Description, Graph, Heading};
-use crate::lib::parser::steps;
+use crate::components::{Content, Description, Graph, Heading, StepPanel};
+use crate::lib::presets::{Preset, PROFILES};
 use crate::lib::profile::analyze;
-use crate::lib::profile::{AnalyzedProfile, PROFILES};
+use crate::lib::shot::ShotSeries;
+use crate::pages::ParseErrorPage;
 use crate::prelude::*;
 
+const BACKEND_ENDPOINT: &str = match option_env!("BACKEND_ENDPOINT") {
+    Some(v) => v,
+    None => "http://localhost:3000",
+};
+
 pub struct ViewerPage {
-    profile_name: String,
-    profile_notes: String,
-    profile_data: AnalyzedProfile,
+    state: State,
+    /// Step clicked on the graph.
+    selected: Option<usize>,
+}
+
+enum State {
+    /// Nothing to show until a file is picked.
+    Empty,
+    Loading,
+    Loaded(Box<Preset>),
+    Failed(String),
+}
+
+pub enum Msg {
+    Open(File),
+    Fetched(Result<Box<Preset>, String>),
+    SelectStep(Option<usize>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,38 +43,166 @@ pub struct QueryParams {
 }
 
 impl Component for ViewerPage {
-    type Message = ();
+    type Message = Msg;
     type Properties = ();
 
     fn create(ctx: &Context<Self>) -> Self {
         let location = ctx.link().location().unwrap();
         let query = location.query::<QueryParams>().unwrap();
 
-        let preset_name = query.preset.unwrap_or_else(|| panic!("Unsupported query"));
-        let preset = PROFILES
-            .iter()
-            .find(|preset| preset.name == preset_name)
-            .unwrap_or_else(|| panic!("Unknown preset: {}", preset_name));
-        let (_, steps) =
-            steps(preset.data.as_bytes()).unwrap_or_else(|e| panic!("Parse error: {:?}", e));
-        let profile = analyze(&steps);
+        let state = match (query.preset, query.visualizer) {
+            (Some(preset_name), _) => {
+                match PROFILES.iter().find(|preset| preset.name == preset_name) {
+                    Some(preset) => State::Loaded(Box::new(preset.clone())),
+                    None => State::Failed(format!("Unknown preset: {}", preset_name)),
+                }
+            }
+            (None, Some(id)) => {
+                ctx.link().send_future(async move {
+                    Msg::Fetched(fetch_visualizer(&id).await.map(Box::new))
+                });
+                State::Loading
+            }
+            (None, None) => State::Empty,
+        };
 
         Self {
-            profile_name: preset.title.clone(),
-            profile_notes: preset.notes.clone(),
-            profile_data: profile,
+            state,
+            selected: None,
         }
     }
 
-    fn view(&self, _: &Context<Self>) -> Html {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Open(file) => {
+                ctx.link()
+                    .send_future(async move { Msg::Fetched(read_file(file).await.map(Box::new)) });
+                self.state = State::Loading;
+                self.selected = None;
+            }
+            Msg::Fetched(Ok(preset)) => self.state = State::Loaded(preset),
+            Msg::Fetched(Err(e)) => self.state = State::Failed(e),
+            Msg::SelectStep(i) => self.selected = i,
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.state {
+            State::Empty => {
+                let onchange = ctx.link().batch_callback(|e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    input.files().and_then(|files| files.get(0)).map(Msg::Open)
+                });
+                html! {
+                    <Page title="Viewer">
+                        <Heading>{ "Open a file" }</Heading>
+                        <Content>
+                            <p>{ "Pick a profile (.tcl) or a shot history file (.shot) saved by the DE1 app. Shots are drawn over the profile they were pulled with." }</p>
+                            <input type="file" accept=".tcl,.shot" {onchange} />
+                        </Content>
+                    </Page>
+                }
+            }
+            State::Loading => html! {
+                <Page title="Viewer">
+                    <Heading>{ "Loading..." }</Heading>
+                </Page>
+            },
+            State::Loaded(preset) => match &preset.error {
+                Some(e) => html! {
+                    <ParseErrorPage name={preset.title.clone()} error={e.clone()} />
+                },
+                None => self.view_preset(ctx, preset),
+            },
+            State::Failed(e) => html! {
+                <Page title="Viewer">
+                    <Heading>{ "Failed to load profile" }</Heading>
+                    <Content>
+                        <pre>{ e.as_str() }</pre>
+                    </Content>
+                </Page>
+            },
+        }
+    }
+}
+
+impl ViewerPage {
+    fn view_preset(&self, ctx: &Context<Self>, preset: &Preset) -> Html {
+        let onselect = ctx.link().callback(Msg::SelectStep);
+        let panel = self.selected.and_then(|i| {
+            let step = preset.steps.get(i)?.clone();
+            Some(html! { <StepPanel index={i} {step} /> })
+        });
+        let notice = preset.encoding.map(|encoding| {
+            html! {
+                <p class={css!(r#"
+                    color: dimgray;
+                    font-size: 14px;
+                "#)}>
+                    { format!("This file is not UTF-8 and was read as {}.", encoding) }
+                </p>
+            }
+        });
         html! {
             <Page title="Viewer">
-                <Heading>{ self.profile_name.as_str() }</Heading>
+                <div lang={preset.language.clone()}>
+                    <Heading>{ preset.title.as_str() }</Heading>
+                </div>
+                { for notice }
                 <Content>
-                    <Graph data={self.profile_data.clone()} />
-                    <Description>{ self.profile_notes.clone() }</Description>
+                    <div class={css!(r#"
+                        display: flex;
+                        align-items: flex-start;
+                        gap: 16px;
+                    "#)}>
+                        <Graph data={analyze(&preset.steps).with_tank_temperature(preset.tank_temperature).with_shot(preset.shot.as_deref().cloned())} {onselect} />
+                        { for panel }
+                    </div>
+                    <div lang={preset.language.clone()}>
+                        <Description>{ preset.notes.clone() }</Description>
+                    </div>
                 </Content>
             </Page>
         }
     }
 }
+
+async fn fetch_visualizer(id: &str) -> Result<Preset, String> {
+    let url = format!("{}/profiles/{}?format=tcl", BACKEND_ENDPOINT, id);
+    let res = Request::get(&url).send().await.map_err(|e| e.to_string())?;
+    if !res.ok() {
+        return Err(format!("{} {}", res.status(), res.status_text()));
+    }
+    let content = res.binary().await.map_err(|e| e.to_string())?;
+    let mut preset = Preset::parse(id, &content).ok_or("Unsupported profile type")?;
+    // NOTE: The profile is still worth showing when the measured curves can't be had
+    match fetch_shot(id).await {
+        Ok(shot) => preset.shot = Some(Box::new(shot)),
+        Err(e) => log::warn!("Failed to fetch shot data: {}", e),
+    }
+    Ok(preset)
+}
+
+async fn fetch_shot(id: &str) -> Result<ShotSeries, String> {
+    let url = format!("{}/shots/{}", BACKEND_ENDPOINT, id);
+    let res = Request::get(&url).send().await.map_err(|e| e.to_string())?;
+    if !res.ok() {
+        return Err(format!("{} {}", res.status(), res.status_text()));
+    }
+    res.json().await.map_err(|e| e.to_string())
+}
+
+async fn read_file(file: File) -> Result<Preset, String> {
+    let file = gloo_file::File::from(file);
+    let content = gloo_file::futures::read_as_bytes(&file)
+        .await
+        .map_err(|e| e.to_string())?;
+    let name = file.name();
+    let preset = if name.ends_with(".shot") {
+        Preset::parse_shot(&name, &content)
+    } else {
+        Preset::parse(&name, &content)
+    };
+    preset.ok_or_else(|| "Unsupported profile type".into())
+}